@@ -0,0 +1,330 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use colored::*;
+use failure::{format_err, Error};
+use utils::Part;
+
+use problems::problem::Solver;
+
+pub(crate) const DEFAULT_WARMUP: usize = 1;
+// regression threshold in percents of the baseline median
+pub(crate) const DEFAULT_THRESHOLD: f64 = 10.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Phase {
+    Parse,
+    Solve(Part),
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Solve(part) => write!(f, "{}", part),
+        }
+    }
+}
+
+impl FromStr for Phase {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parse" => Ok(Phase::Parse),
+            _ => Ok(Phase::Solve(s.parse()?)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Stats {
+    pub(crate) min: Duration,
+    pub(crate) median: Duration,
+    pub(crate) p95: Duration,
+}
+
+impl Stats {
+    pub(crate) fn new(mut samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty());
+        samples.sort_unstable();
+
+        let n = samples.len();
+        let median = if n.is_multiple_of(2) {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2
+        } else {
+            samples[n / 2]
+        };
+
+        // nearest-rank percentile
+        let p95 = samples[(n * 95).div_ceil(100) - 1];
+
+        Stats {
+            min: samples[0],
+            median,
+            p95,
+        }
+    }
+}
+
+pub(crate) struct BenchReport {
+    pub(crate) day: usize,
+    pub(crate) title: &'static str,
+    pub(crate) phases: Vec<(Phase, Stats)>,
+}
+
+// runs parse and every part `warmup + iterations` times, only the last `iterations` are measured,
+// parse and parts are timed separately
+pub(crate) fn bench(
+    solver: &dyn Solver,
+    input: &str,
+    parts: &[Part],
+    iterations: usize,
+    warmup: usize,
+) -> Result<BenchReport, Error> {
+    let mut samples: Vec<(Phase, Vec<Duration>)> = Some(Phase::Parse)
+        .into_iter()
+        .chain(parts.iter().map(|part| Phase::Solve(*part)))
+        .map(|phase| (phase, Vec::with_capacity(iterations)))
+        .collect();
+
+    for iter in 0..warmup + iterations {
+        let now = Instant::now();
+        let parsed = solver.parse(input)?;
+        let mut elapsed = vec![now.elapsed()];
+
+        for part in parts {
+            let now = Instant::now();
+            solver
+                .solve(parsed.as_ref(), *part)
+                .map_err(|err| format_err!("day {} part {}: {}", solver.day(), part, err))?;
+            elapsed.push(now.elapsed());
+        }
+
+        if iter >= warmup {
+            for ((_, phase_samples), sample) in samples.iter_mut().zip(elapsed) {
+                phase_samples.push(sample);
+            }
+        }
+    }
+
+    Ok(BenchReport {
+        day: solver.day(),
+        title: solver.title(),
+        phases: samples
+            .into_iter()
+            .map(|(phase, phase_samples)| (phase, Stats::new(phase_samples)))
+            .collect(),
+    })
+}
+
+// medians of a previous run, the keys are day and phase
+pub(crate) type Baseline = HashMap<(usize, Phase), Duration>;
+
+// every non-empty line which isn't a comment is `<day> <parse|1|2> <median in microseconds>`
+pub(crate) fn parse_baseline(input_raw: &str) -> Result<Baseline, Error> {
+    let mut baseline = HashMap::new();
+
+    for (line_idx, line) in input_raw.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (day, phase, median) = match fields.as_slice() {
+            [day, phase, median] => (day, phase, median),
+            _ => {
+                return Err(format_err!(
+                    "line {}: expected '<day> <phase> <median_us>'",
+                    line_idx + 1
+                ))
+            }
+        };
+
+        let day = day
+            .parse::<usize>()
+            .map_err(|_| format_err!("line {}: wrong day '{}'", line_idx + 1, day))?;
+        let phase = phase.parse::<Phase>().map_err(|_| {
+            format_err!(
+                "line {}: wrong phase '{}', expected parse, 1 or 2",
+                line_idx + 1,
+                phase
+            )
+        })?;
+        let median = median
+            .parse::<f64>()
+            .ok()
+            .filter(|median| median.is_finite() && *median >= 0.0)
+            .ok_or_else(|| format_err!("line {}: wrong median '{}'", line_idx + 1, median))?;
+
+        baseline.insert((day, phase), Duration::from_secs_f64(median / 1e6));
+    }
+
+    Ok(baseline)
+}
+
+pub(crate) fn format_baseline(reports: &[BenchReport]) -> String {
+    let mut lines = vec!["# <day> <phase> <median in microseconds>".to_owned()];
+
+    for report in reports {
+        for (phase, stats) in &report.phases {
+            lines.push(format!("{} {} {}", report.day, phase, micros(stats.median)));
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+// relative change of the median against the baseline, in percents
+fn change(baseline: Duration, median: Duration) -> f64 {
+    if baseline.as_nanos() == 0 {
+        return 0.0;
+    }
+
+    (median.as_nanos() as f64 / baseline.as_nanos() as f64 - 1.0) * 100.0
+}
+
+fn micros(d: Duration) -> String {
+    format!("{:.1}", d.as_nanos() as f64 / 1000.0)
+}
+
+// prints a table of timings, returns false if any phase got slower than `threshold` percents
+pub(crate) fn print(reports: &[BenchReport], baseline: Option<&Baseline>, threshold: f64) -> bool {
+    println!(
+        "{:>3}  {:<24}  {:>5}  {:>12}  {:>12}  {:>12}  {:>12}  {:>8}",
+        "day".bold(),
+        "title".bold(),
+        "phase".bold(),
+        "min, us".bold(),
+        "median, us".bold(),
+        "p95, us".bold(),
+        "baseline, us".bold(),
+        "change".bold()
+    );
+
+    let mut regressions = 0;
+
+    for report in reports {
+        for (phase, stats) in &report.phases {
+            let (base, diff) = match baseline.and_then(|b| b.get(&(report.day, *phase))) {
+                Some(base) => {
+                    let change = change(*base, stats.median);
+                    let diff = format!("{:>+7.1}%", change);
+
+                    let diff = if change > threshold {
+                        regressions += 1;
+                        diff.bold().red()
+                    } else if change < -threshold {
+                        diff.green()
+                    } else {
+                        diff.normal()
+                    };
+
+                    (micros(*base), diff)
+                }
+                None => ("-".to_owned(), "-".normal()),
+            };
+
+            println!(
+                "{:>3}  {:<24}  {:>5}  {:>12}  {:>12}  {:>12}  {:>12}  {:>8}",
+                report.day,
+                report.title,
+                phase,
+                micros(stats.min),
+                micros(stats.median),
+                micros(stats.p95),
+                base,
+                diff
+            );
+        }
+    }
+
+    if baseline.is_some() {
+        println!();
+        if regressions == 0 {
+            println!(
+                "{} no regressions above {}%",
+                "OK:".bold().green(),
+                threshold
+            );
+        } else {
+            println!(
+                "{} {} phases are more than {}% slower than the baseline",
+                "REGRESSED:".bold().red(),
+                regressions,
+                threshold
+            );
+        }
+    }
+
+    regressions == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{change, format_baseline, parse_baseline, BenchReport, Phase, Stats};
+    use std::time::Duration;
+    use utils::Part;
+
+    fn micros(v: &[u64]) -> Vec<Duration> {
+        v.iter().map(|x| Duration::from_micros(*x)).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::new(micros(&[5, 1, 3]));
+        assert_eq!(stats.min, Duration::from_micros(1));
+        assert_eq!(stats.median, Duration::from_micros(3));
+        assert_eq!(stats.p95, Duration::from_micros(5));
+
+        let stats = Stats::new(micros(&[4, 1, 3, 2]));
+        assert_eq!(
+            stats.median,
+            Duration::from_micros(2) + Duration::from_nanos(500)
+        );
+
+        let stats = Stats::new(micros(&(1..=100).collect::<Vec<_>>()));
+        assert_eq!(stats.min, Duration::from_micros(1));
+        assert_eq!(stats.p95, Duration::from_micros(95));
+    }
+
+    #[test]
+    fn test_baseline() {
+        let report = BenchReport {
+            day: 7,
+            title: "Handy Haversacks",
+            phases: vec![
+                (Phase::Parse, Stats::new(vec![Duration::from_nanos(10_500)])),
+                (Phase::Solve(Part::Second), Stats::new(micros(&[20]))),
+            ],
+        };
+
+        let baseline = parse_baseline(&format_baseline(&[report])).unwrap();
+
+        assert_eq!(baseline.len(), 2);
+        assert_eq!(baseline[&(7, Phase::Parse)], Duration::from_nanos(10_500));
+        assert_eq!(
+            baseline[&(7, Phase::Solve(Part::Second))],
+            Duration::from_micros(20)
+        );
+
+        assert!(parse_baseline("7 3 10").is_err());
+        assert!(parse_baseline("7 parse").is_err());
+        assert!(parse_baseline("7 parse x").is_err());
+        assert!(parse_baseline("7 parse -1").is_err());
+    }
+
+    #[test]
+    fn test_change() {
+        let base = Duration::from_micros(100);
+
+        assert!((change(base, Duration::from_micros(120)) - 20.0).abs() < 1e-9);
+        assert!((change(base, Duration::from_micros(50)) + 50.0).abs() < 1e-9);
+        assert_eq!(change(Duration::default(), base), 0.0);
+    }
+}
@@ -0,0 +1,606 @@
+use failure::{format_err, Error};
+use utils::Part;
+
+use problems::{
+    problem18::Precedence,
+    render::{Palette, MAX_FRAME_SIDE},
+};
+
+use crate::format::Format;
+
+pub(crate) const USAGE: &str = "\
+usage: problems [DAYS...] [--part 1|2] [--input PATH] [--verify] [--format json|csv|text]
+                [--timeout SECS] [--list]
+       problems [DAYS...] [--part 1|2] [--input PATH] --bench N [--warmup N]
+                [--baseline PATH] [--save-baseline PATH] [--threshold PERCENT]
+       problems 17 --dimensions N [--input PATH] [--timeout SECS]
+       problems 11|17 --render PATH [--part 1|2] [--input PATH] [--cell-size N] [--palette SPEC]
+       problems 8 --debug [--input PATH]
+       problems 18 --reduce EXPR [--part 1|2 | --precedence SPEC]
+       problems 19 --explain MESSAGES [--part 1|2] [--input PATH]
+
+    DAYS          days to run, either a single day (`7`) or a range (`13..19`, `13..=19`),
+                  all available days are run if none given
+    -p, --part    run only the first (1) or the second (2) star of each day
+    -i, --input   read puzzle input from PATH (`-` for stdin) instead of the embedded one,
+                  requires exactly one day to be selected
+    -v, --verify  compare answers with the known ones and print a pass/fail table,
+                  can't be used together with `--input`
+    -f, --format  output format, `json` and `csv` print one record per day and part with
+                  the answer, error, elapsed time and worker id, `text` is the default
+    -t, --timeout stop a day once it takes more than SECS seconds and report it as timed out,
+                  long loops check the deadline every now and then, so it isn't exact
+    -l, --list    list available days and exit
+    -b, --bench   time parse and every part separately over N runs one day after another
+                  and print min, median and 95th percentile
+    --warmup      number of unmeasured runs before the measured ones, 1 by default
+    --baseline    compare medians with the ones saved to PATH before, fails if any of them
+                  is slower than the threshold
+    --save-baseline
+                  save medians to PATH to compare with later
+    --threshold   regression threshold in percents, 10 by default
+    -d, --dimensions
+                  run day 17 in N dimensions instead of 3 and 4 of its two stars, from 2 to 10
+    -r, --render  draw every generation of day 11 seating (of the star chosen with `--part`,
+                  the first one by default) or of day 17 z-slices to PATH, an animated gif
+                  if it ends with `.gif` and a numbered sequence of png files otherwise
+    --cell-size   side of a cell in pixels, 4 by default
+    --palette     colors of cells by their characters, e.g. `#=ff8000,L=4060a0,.=202020`
+    --debug       step through day 8 boot code in an interactive debugger with breakpoints,
+                  a watch on the accumulator and an execution trace, `help` lists its commands
+    --reduce      print how day 18 groups and reduces EXPR step by step under the precedence
+                  of the star chosen with `--part`, the first one by default
+    --precedence  precedence table for `--reduce` instead of one of the stars, e.g. `+=2,*=1`,
+                  levels start from 1 and the higher one binds tighter, `r` after the level
+                  makes the operator right associative
+    --explain     print how day 19 messages match rule 0 under the rules of the star chosen
+                  with `--part`, the first one by default, or how far they get if they don't,
+                  MESSAGES are numbers of messages from 1 and their ranges, e.g. `1,4..6`
+    -h, --help    print this message and exit";
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Options {
+    pub(crate) days: Vec<usize>,
+    pub(crate) part: Option<Part>,
+    pub(crate) input: Option<String>,
+    pub(crate) verify: bool,
+    pub(crate) format: Format,
+    pub(crate) timeout: Option<f64>,
+    pub(crate) list: bool,
+    pub(crate) bench: Option<usize>,
+    pub(crate) warmup: Option<usize>,
+    pub(crate) baseline: Option<String>,
+    pub(crate) save_baseline: Option<String>,
+    pub(crate) threshold: Option<f64>,
+    pub(crate) dimensions: Option<usize>,
+    pub(crate) render: Option<String>,
+    pub(crate) cell_size: Option<u32>,
+    pub(crate) palette: Option<Palette>,
+    pub(crate) debug: bool,
+    pub(crate) reduce: Option<String>,
+    pub(crate) precedence: Option<Precedence>,
+    pub(crate) explain: Option<Vec<usize>>,
+    pub(crate) help: bool,
+}
+
+fn parse_day(s: &str) -> Result<usize, Error> {
+    s.parse::<usize>()
+        .map_err(|_| format_err!("wrong day '{}'", s))
+}
+
+fn parse_number<T: std::str::FromStr>(opt: &str, value: Option<String>) -> Result<T, Error> {
+    let value = value.ok_or_else(|| format_err!("missing value for '{}'", opt))?;
+
+    value
+        .parse::<T>()
+        .map_err(|_| format_err!("wrong value '{}' for '{}'", value, opt))
+}
+
+fn parse_days(s: &str) -> Result<Vec<usize>, Error> {
+    if let Some(idx) = s.find("..") {
+        let from = parse_day(&s[..idx])?;
+        let tail = &s[idx + 2..];
+
+        // both `a..b` and `a..=b` are inclusive, the same way as days are counted
+        let to = parse_day(tail.strip_prefix('=').unwrap_or(tail))?;
+
+        if from > to {
+            return Err(format_err!("empty range of days '{}'", s));
+        }
+
+        return Ok((from..=to).collect());
+    }
+
+    Ok(vec![parse_day(s)?])
+}
+
+// numbers of messages and their inclusive ranges separated with commas
+fn parse_messages(s: &str) -> Result<Vec<usize>, Error> {
+    let number = |n: &str| match n.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format_err!("wrong message number '{}'", n)),
+    };
+
+    let mut res = vec![];
+
+    for item in s.split(',') {
+        match item.split_once("..") {
+            Some((from, to)) => {
+                let (from, to) = (number(from)?, number(to.strip_prefix('=').unwrap_or(to))?);
+                if from > to {
+                    return Err(format_err!("empty range of messages '{}'", item));
+                }
+                res.extend(from..=to);
+            }
+            None => res.push(number(item)?),
+        }
+    }
+
+    Ok(res)
+}
+
+pub(crate) fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, Error> {
+    let mut opts = Options::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-p" | "--part" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format_err!("missing value for '{}'", arg))?;
+                opts.part = Some(value.parse::<Part>()?);
+            }
+            "-i" | "--input" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format_err!("missing value for '{}'", arg))?;
+                opts.input = Some(value);
+            }
+            "-v" | "--verify" => opts.verify = true,
+            "-f" | "--format" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format_err!("missing value for '{}'", arg))?;
+                opts.format = value.parse::<Format>()?;
+            }
+            "-t" | "--timeout" => opts.timeout = Some(parse_number(&arg, args.next())?),
+            "-l" | "--list" => opts.list = true,
+            "-b" | "--bench" => opts.bench = Some(parse_number(&arg, args.next())?),
+            "--warmup" => opts.warmup = Some(parse_number(&arg, args.next())?),
+            "--threshold" => opts.threshold = Some(parse_number(&arg, args.next())?),
+            "--baseline" | "--save-baseline" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format_err!("missing value for '{}'", arg))?;
+
+                if arg == "--baseline" {
+                    opts.baseline = Some(value);
+                } else {
+                    opts.save_baseline = Some(value);
+                }
+            }
+            "-d" | "--dimensions" => opts.dimensions = Some(parse_number(&arg, args.next())?),
+            "-r" | "--render" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format_err!("missing value for '{}'", arg))?;
+                opts.render = Some(value);
+            }
+            "--cell-size" => opts.cell_size = Some(parse_number(&arg, args.next())?),
+            "--palette" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format_err!("missing value for '{}'", arg))?;
+                opts.palette = Some(value.parse::<Palette>()?);
+            }
+            "--debug" => opts.debug = true,
+            "--reduce" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format_err!("missing value for '{}'", arg))?;
+                opts.reduce = Some(value);
+            }
+            "--precedence" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format_err!("missing value for '{}'", arg))?;
+                opts.precedence = Some(value.parse::<Precedence>()?);
+            }
+            "--explain" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format_err!("missing value for '{}'", arg))?;
+                opts.explain = Some(parse_messages(&value)?);
+            }
+            "-h" | "--help" => opts.help = true,
+            _ if arg.starts_with('-') => return Err(format_err!("unknown option '{}'", arg)),
+            _ => opts.days.extend(parse_days(&arg)?),
+        }
+    }
+
+    opts.days.sort_unstable();
+    opts.days.dedup();
+
+    if opts.input.is_some() && opts.days.len() != 1 {
+        return Err(format_err!(
+            "'--input' requires exactly one day to be selected"
+        ));
+    }
+
+    if opts.input.is_some() && opts.verify {
+        return Err(format_err!(
+            "known answers are for embedded inputs only, '--verify' can't be used with '--input'"
+        ));
+    }
+
+    if opts.verify && opts.format != Format::Text {
+        return Err(format_err!("'--verify' prints a text table only"));
+    }
+
+    if opts.bench.is_none()
+        && (opts.warmup.is_some()
+            || opts.baseline.is_some()
+            || opts.save_baseline.is_some()
+            || opts.threshold.is_some())
+    {
+        return Err(format_err!(
+            "'--warmup', '--baseline', '--save-baseline' and '--threshold' require '--bench'"
+        ));
+    }
+
+    if let Some(timeout) = opts.timeout {
+        if !timeout.is_finite() || timeout <= 0.0 {
+            return Err(format_err!(
+                "'--timeout' must be a positive number of seconds"
+            ));
+        }
+    }
+
+    if opts.bench == Some(0) {
+        return Err(format_err!("'--bench' requires at least one run"));
+    }
+
+    if opts.bench.is_some()
+        && (opts.verify || opts.format != Format::Text || opts.timeout.is_some())
+    {
+        return Err(format_err!(
+            "'--bench' can't be used together with '--verify', '--format' or '--timeout'"
+        ));
+    }
+
+    if opts.dimensions.is_some() {
+        if opts.days != [17] {
+            return Err(format_err!(
+                "'--dimensions' requires day 17 to be selected alone"
+            ));
+        }
+
+        if opts.part.is_some() || opts.verify || opts.bench.is_some() || opts.format != Format::Text
+        {
+            return Err(format_err!(
+                "'--dimensions' can't be used together with '--part', '--verify', '--bench' or '--format'"
+            ));
+        }
+    }
+
+    if opts.render.is_none() && (opts.cell_size.is_some() || opts.palette.is_some()) {
+        return Err(format_err!(
+            "'--cell-size' and '--palette' require '--render'"
+        ));
+    }
+
+    if opts.cell_size == Some(0) {
+        return Err(format_err!("'--cell-size' must be at least one pixel"));
+    }
+
+    if opts.cell_size.is_some_and(|size| size > MAX_FRAME_SIDE) {
+        return Err(format_err!(
+            "'--cell-size' must be at most {} pixels",
+            MAX_FRAME_SIDE
+        ));
+    }
+
+    if opts.render.is_some() {
+        if opts.days != [11] && opts.days != [17] {
+            return Err(format_err!(
+                "'--render' requires either day 11 or day 17 to be selected alone"
+            ));
+        }
+
+        if opts.days == [17] && opts.part == Some(Part::Second) {
+            return Err(format_err!("only the first star of day 17 can be rendered"));
+        }
+
+        if opts.verify
+            || opts.bench.is_some()
+            || opts.dimensions.is_some()
+            || opts.format != Format::Text
+        {
+            return Err(format_err!(
+                "'--render' can't be used together with '--verify', '--bench', '--dimensions' or '--format'"
+            ));
+        }
+    }
+
+    if opts.debug {
+        if opts.days != [8] {
+            return Err(format_err!("'--debug' requires day 8 to be selected alone"));
+        }
+
+        if opts.part.is_some()
+            || opts.verify
+            || opts.bench.is_some()
+            || opts.render.is_some()
+            || opts.format != Format::Text
+            || opts.timeout.is_some()
+        {
+            return Err(format_err!(
+                "'--debug' can only be used together with '--input'"
+            ));
+        }
+    }
+
+    if opts.precedence.is_some() && opts.reduce.is_none() {
+        return Err(format_err!("'--precedence' requires '--reduce'"));
+    }
+
+    if opts.reduce.is_some() {
+        if opts.days != [18] {
+            return Err(format_err!(
+                "'--reduce' requires day 18 to be selected alone"
+            ));
+        }
+
+        if opts.part.is_some() && opts.precedence.is_some() {
+            return Err(format_err!(
+                "'--part' and '--precedence' can't be used together"
+            ));
+        }
+
+        if opts.input.is_some()
+            || opts.verify
+            || opts.bench.is_some()
+            || opts.format != Format::Text
+            || opts.timeout.is_some()
+        {
+            return Err(format_err!(
+                "'--reduce' can only be used together with '--part' or '--precedence'"
+            ));
+        }
+    }
+
+    if opts.explain.is_some() {
+        if opts.days != [19] {
+            return Err(format_err!(
+                "'--explain' requires day 19 to be selected alone"
+            ));
+        }
+
+        if opts.verify
+            || opts.bench.is_some()
+            || opts.format != Format::Text
+            || opts.timeout.is_some()
+        {
+            return Err(format_err!(
+                "'--explain' can only be used together with '--part' or '--input'"
+            ));
+        }
+    }
+
+    Ok(opts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, Options};
+    use crate::format::Format;
+    use utils::Part;
+
+    fn parse(args: &[&str]) -> Options {
+        parse_args(args.iter().map(|s| s.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_days() {
+        assert!(parse(&[]).days.is_empty());
+        assert_eq!(parse(&["7"]).days, vec![7]);
+        assert_eq!(parse(&["7", "13..=15"]).days, vec![7, 13, 14, 15]);
+        assert_eq!(parse(&["3..5", "4", "1"]).days, vec![1, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_part() {
+        assert_eq!(parse(&["7", "--part", "2"]).part, Some(Part::Second));
+        assert_eq!(parse(&["-p", "1"]).part, Some(Part::First));
+        assert_eq!(parse(&["7"]).part, None);
+    }
+
+    #[test]
+    fn test_input() {
+        assert_eq!(parse(&["7", "--input", "-"]).input, Some("-".to_owned()));
+        assert_eq!(
+            parse(&["-i", "my_input", "7"]).input,
+            Some("my_input".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        assert!(parse(&["--verify"]).verify);
+        assert!(parse(&["1..5", "-v", "-p", "1"]).verify);
+        assert!(!parse(&["1..5"]).verify);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(parse(&[]).format, Format::Text);
+        assert_eq!(parse(&["--format", "json"]).format, Format::Json);
+        assert_eq!(parse(&["1..5", "-f", "csv"]).format, Format::Csv);
+    }
+
+    #[test]
+    fn test_timeout() {
+        assert_eq!(parse(&["1..5"]).timeout, None);
+        assert_eq!(parse(&["--timeout", "2.5"]).timeout, Some(2.5));
+        assert_eq!(parse(&["-t", "10", "13"]).timeout, Some(10.0));
+    }
+
+    #[test]
+    fn test_bench() {
+        let opts = parse(&["1..5", "--bench", "10"]);
+        assert_eq!(opts.bench, Some(10));
+        assert_eq!(opts.warmup, None);
+
+        let opts = parse(&[
+            "-b",
+            "3",
+            "--warmup",
+            "2",
+            "--baseline",
+            "old",
+            "--save-baseline",
+            "new",
+            "--threshold",
+            "5.5",
+        ]);
+        assert_eq!(opts.warmup, Some(2));
+        assert_eq!(opts.baseline, Some("old".to_owned()));
+        assert_eq!(opts.save_baseline, Some("new".to_owned()));
+        assert_eq!(opts.threshold, Some(5.5));
+    }
+
+    #[test]
+    fn test_dimensions() {
+        assert_eq!(parse(&["17"]).dimensions, None);
+        assert_eq!(parse(&["17", "--dimensions", "5"]).dimensions, Some(5));
+        assert_eq!(parse(&["-d", "6", "17", "-t", "10"]).dimensions, Some(6));
+    }
+
+    #[test]
+    fn test_render() {
+        let opts = parse(&["11", "--render", "seats.gif", "-p", "2"]);
+        assert_eq!(opts.render, Some("seats.gif".to_owned()));
+        assert_eq!(opts.cell_size, None);
+
+        let opts = parse(&[
+            "17",
+            "-r",
+            "cubes.png",
+            "--cell-size",
+            "8",
+            "--palette",
+            "#=ffffff",
+        ]);
+        assert_eq!(opts.cell_size, Some(8));
+        assert_eq!(opts.palette, Some("#=ffffff".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_debug() {
+        assert!(!parse(&["8"]).debug);
+
+        let opts = parse(&["8", "--debug", "-i", "boot.txt"]);
+        assert!(opts.debug);
+        assert_eq!(opts.input, Some("boot.txt".to_owned()));
+    }
+
+    #[test]
+    fn test_reduce() {
+        let opts = parse(&["18", "--reduce", "1 + 2 * 3", "-p", "2"]);
+        assert_eq!(opts.reduce, Some("1 + 2 * 3".to_owned()));
+        assert_eq!(opts.precedence, None);
+
+        let opts = parse(&["18", "--reduce", "1 - 2 - 3", "--precedence", "-=1r"]);
+        assert_eq!(opts.precedence, Some("-=1r".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_explain() {
+        assert_eq!(parse(&["19"]).explain, None);
+
+        let opts = parse(&["19", "--explain", "3,1..=2,7..8", "-p", "2"]);
+        assert_eq!(opts.explain, Some(vec![3, 1, 2, 7, 8]));
+        assert_eq!(opts.part, Some(Part::Second));
+    }
+
+    #[test]
+    fn test_errors() {
+        let parse_err = |args: &[&str]| parse_args(args.iter().map(|s| s.to_string())).is_err();
+
+        assert!(parse_err(&["--part"]));
+        assert!(parse_err(&["--part", "3"]));
+        assert!(parse_err(&["5..3"]));
+        assert!(parse_err(&["x"]));
+        assert!(parse_err(&["--verbose"]));
+        assert!(parse_err(&["--input", "my_input"]));
+        assert!(parse_err(&["1..2", "--input", "my_input"]));
+        assert!(parse_err(&["1", "--input", "my_input", "--verify"]));
+        assert!(parse_err(&["--format", "xml"]));
+        assert!(parse_err(&["--verify", "--format", "json"]));
+        assert!(parse_err(&["--bench", "x"]));
+        assert!(parse_err(&["--bench", "0"]));
+        assert!(parse_err(&["--warmup", "2"]));
+        assert!(parse_err(&["--bench", "2", "--verify"]));
+        assert!(parse_err(&["--bench", "2", "--format", "csv"]));
+        assert!(parse_err(&["--timeout", "0"]));
+        assert!(parse_err(&["--timeout", "-1"]));
+        assert!(parse_err(&["--timeout", "soon"]));
+        assert!(parse_err(&["--bench", "2", "--timeout", "1"]));
+        assert!(parse_err(&["--dimensions", "5"]));
+        assert!(parse_err(&["16..17", "--dimensions", "5"]));
+        assert!(parse_err(&["17", "--dimensions", "5", "--part", "1"]));
+        assert!(parse_err(&["17", "--dimensions", "5", "--bench", "3"]));
+        assert!(parse_err(&["11", "--cell-size", "8"]));
+        assert!(parse_err(&[
+            "11",
+            "--render",
+            "seats.gif",
+            "--cell-size",
+            "0"
+        ]));
+        assert!(parse_err(&[
+            "11",
+            "--render",
+            "seats.gif",
+            "--cell-size",
+            "100000000"
+        ]));
+        assert!(parse_err(&[
+            "11",
+            "--render",
+            "seats.gif",
+            "--palette",
+            "#"
+        ]));
+        assert!(parse_err(&["10..11", "--render", "seats.gif"]));
+        assert!(parse_err(&["17", "--render", "cubes.gif", "--part", "2"]));
+        assert!(parse_err(&["11", "--render", "seats.gif", "--verify"]));
+        assert!(parse_err(&["--debug"]));
+        assert!(parse_err(&["7..8", "--debug"]));
+        assert!(parse_err(&["8", "--debug", "--part", "2"]));
+        assert!(parse_err(&["17", "--reduce", "1 + 2"]));
+        assert!(parse_err(&["18", "--precedence", "+=1"]));
+        assert!(parse_err(&[
+            "18",
+            "--reduce",
+            "1 + 2",
+            "--precedence",
+            "+=0"
+        ]));
+        assert!(parse_err(&[
+            "18",
+            "--reduce",
+            "1",
+            "-p",
+            "1",
+            "--precedence",
+            "+=1"
+        ]));
+        assert!(parse_err(&["18", "--reduce", "1 + 2", "--verify"]));
+        assert!(parse_err(&["18", "--explain", "1"]));
+        assert!(parse_err(&["19", "--explain", "0"]));
+        assert!(parse_err(&["19", "--explain", "3..1"]));
+        assert!(parse_err(&["19", "--explain", "1,,2"]));
+        assert!(parse_err(&["19", "--explain", "1", "--format", "json"]));
+    }
+}
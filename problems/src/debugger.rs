@@ -0,0 +1,231 @@
+use std::io::{self, BufRead, Write};
+
+use problems::problem8::{register_name, Event, Op, Vm};
+
+const HELP: &str = "\
+commands:
+    s, step [N]       execute N instructions, 1 by default
+    c, continue       run until termination, a loop, a breakpoint or a watched change of acc,
+                      continuing from a loop goes one more round
+    b, break IDX      set a breakpoint on the instruction IDX
+    d, delete IDX     remove the breakpoint from the instruction IDX
+    w, watch          toggle stopping on every change of acc
+    t, trace [N]      print the last N executed instructions, 10 by default, up to 1000
+    l, list [IDX]     print instructions around IDX, the current one by default
+    p, print          print the state of the machine, its registers and output
+    r, reset          start the program over, breakpoints and watch are kept
+    h, help           print this message
+    q, quit           leave the debugger";
+
+// number of instructions `list` prints on each side of the given one
+const LIST_AROUND: usize = 4;
+
+fn state(vm: &Vm) -> String {
+    let current = vm
+        .current()
+        .map(|op| op.to_string())
+        .unwrap_or_else(|| "<outside>".to_owned());
+
+    format!(
+        "ip {} ({}), acc {}, {} steps",
+        vm.ip(),
+        current,
+        vm.acc(),
+        vm.steps()
+    )
+}
+
+fn parse_index(arg: Option<&str>) -> Result<usize, String> {
+    let arg = arg.ok_or_else(|| "missing instruction index".to_owned())?;
+    arg.parse()
+        .map_err(|_| format!("wrong instruction index '{}'", arg))
+}
+
+fn list(vm: &Vm, around: usize, out: &mut impl Write) -> io::Result<()> {
+    let program = vm.program();
+    let breakpoints = vm.breakpoints();
+
+    let from = around.saturating_sub(LIST_AROUND);
+    let to = (around + LIST_AROUND + 1).min(program.len());
+
+    for (idx, op) in program.iter().enumerate().take(to).skip(from) {
+        let marker = if idx as isize == vm.ip() { '>' } else { ' ' };
+        let breakpoint = if breakpoints.contains(&idx) { '*' } else { ' ' };
+        writeln!(out, "{}{} {:>5}: {}", marker, breakpoint, idx, op)?;
+    }
+
+    Ok(())
+}
+
+// executes a single command, returns false once the session is over
+fn command(vm: &mut Vm, line: &str, out: &mut impl Write) -> io::Result<bool> {
+    let mut words = line.split_whitespace();
+    let (cmd, arg) = match words.next() {
+        Some(cmd) => (cmd, words.next()),
+        None => return Ok(true),
+    };
+
+    match cmd {
+        "s" | "step" => {
+            let count = match arg.map(str::parse::<usize>) {
+                None => 1,
+                Some(Ok(count)) if count > 0 => count,
+                _ => {
+                    writeln!(out, "wrong number of steps '{}'", arg.unwrap_or_default())?;
+                    return Ok(true);
+                }
+            };
+
+            let mut event = Event::Stepped;
+            for _ in 0..count {
+                event = vm.step();
+                if event != Event::Stepped {
+                    break;
+                }
+            }
+
+            if event != Event::Stepped {
+                writeln!(out, "{}", event)?;
+            }
+            writeln!(out, "{}", state(vm))?;
+        }
+        "c" | "continue" => {
+            let event = vm.resume();
+            writeln!(out, "{}\n{}", event, state(vm))?;
+        }
+        "b" | "break" => match parse_index(arg) {
+            Ok(idx) if vm.add_breakpoint(idx) => writeln!(out, "breakpoint set at {}", idx)?,
+            Ok(idx) => writeln!(out, "there is no instruction {}", idx)?,
+            Err(err) => writeln!(out, "{}", err)?,
+        },
+        "d" | "delete" => match parse_index(arg) {
+            Ok(idx) if vm.remove_breakpoint(idx) => {
+                writeln!(out, "breakpoint removed from {}", idx)?
+            }
+            Ok(idx) => writeln!(out, "there is no breakpoint at {}", idx)?,
+            Err(err) => writeln!(out, "{}", err)?,
+        },
+        "w" | "watch" => {
+            vm.set_watch_acc(!vm.watch_acc());
+            let status = if vm.watch_acc() { "on" } else { "off" };
+            writeln!(out, "watch on acc is {}", status)?;
+        }
+        "t" | "trace" => match arg.map(str::parse::<usize>).unwrap_or(Ok(10)) {
+            Ok(count) => {
+                let trace = vm.trace();
+                for entry in trace.iter().skip(trace.len().saturating_sub(count)) {
+                    writeln!(out, "{}", entry)?;
+                }
+            }
+            Err(_) => writeln!(out, "wrong number of entries '{}'", arg.unwrap_or_default())?,
+        },
+        "l" | "list" => {
+            let around = match arg {
+                Some(_) => parse_index(arg),
+                None => Ok(vm.ip().max(0) as usize),
+            };
+
+            match around {
+                Ok(around) => list(vm, around, out)?,
+                Err(err) => writeln!(out, "{}", err)?,
+            }
+        }
+        "p" | "print" => {
+            writeln!(out, "{}", state(vm))?;
+            let registers: Vec<_> = vm
+                .registers()
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| format!("{} = {}", register_name(idx), value))
+                .collect();
+            let output: Vec<_> = vm.output().iter().map(|v| v.to_string()).collect();
+            writeln!(out, "registers: {}", registers.join(", "))?;
+            writeln!(out, "output: {}", output.join(", "))?;
+            let breakpoints: Vec<_> = vm.breakpoints().iter().map(|b| b.to_string()).collect();
+            writeln!(out, "breakpoints: {}", breakpoints.join(", "))?;
+        }
+        "r" | "reset" => {
+            vm.reset();
+            writeln!(out, "{}", state(vm))?;
+        }
+        "h" | "help" => writeln!(out, "{}", HELP)?,
+        "q" | "quit" => return Ok(false),
+        _ => writeln!(out, "unknown command '{}', type 'help' for the list", cmd)?,
+    }
+
+    Ok(true)
+}
+
+// interactive session over the program, commands are read line by line until `quit`
+// or the end of the input
+pub(crate) fn run(program: &[Op], input: impl BufRead, mut out: impl Write) -> io::Result<()> {
+    let mut vm = Vm::new(program);
+    vm.set_tracing(true);
+
+    writeln!(
+        out,
+        "{} instructions loaded, type 'help' for the list of commands",
+        program.len()
+    )?;
+    writeln!(out, "{}", state(&vm))?;
+
+    let mut lines = input.lines();
+
+    loop {
+        write!(out, "(day8) ")?;
+        out.flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+
+        if !command(&mut vm, &line, &mut out)? {
+            break;
+        }
+    }
+
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run;
+    use problems::problem8::parse;
+
+    const RAW_INPUT: &str =
+        "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    fn session(commands: &str) -> String {
+        let mut out = vec![];
+        run(&parse(RAW_INPUT).unwrap(), commands.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_session() {
+        let out = session("break 6\nc\nstep 2\nw\nc\nw\ndelete 6\nc\ntrace 2\np\nq\nstep\n");
+
+        assert!(out.contains("breakpoint set at 6\n"));
+        assert!(out.contains("breakpoint at 6\nip 6 (acc +1), acc 1, 3 steps\n"));
+        assert!(out.contains("ip 3 (acc +3), acc 2, 5 steps\n"));
+        assert!(out.contains("acc changed from 2 to 5 at 3\n"));
+        assert!(out.contains("instruction 4 is about to be executed twice\n"));
+        assert!(out.contains("    7: jmp -4     acc 7 -> 7\n    3: acc +3     acc 7 -> 10\n"));
+        // nothing runs after quit
+        assert!(out.contains("acc 10, 12 steps\n"));
+        assert!(out.contains("registers: a = 10, b = 0, c = 0, d = 0\n"));
+        assert!(!out.contains("13 steps"));
+    }
+
+    #[test]
+    fn test_mistakes() {
+        let out = session("break 42\nbreak x\ndelete 1\nstep 0\nfly\n");
+
+        assert!(out.contains("there is no instruction 42\n"));
+        assert!(out.contains("wrong instruction index 'x'\n"));
+        assert!(out.contains("there is no breakpoint at 1\n"));
+        assert!(out.contains("wrong number of steps '0'\n"));
+        assert!(out.contains("unknown command 'fly'"));
+    }
+}
@@ -0,0 +1,215 @@
+use std::str::FromStr;
+
+use failure::{format_err, Error};
+use serde::Serialize;
+use utils::Answer;
+
+use problems::runner::{Outcome, Report};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) enum Format {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format_err!(
+                "wrong format '{}', expected json, csv or text",
+                s
+            )),
+        }
+    }
+}
+
+// single line of machine readable output, there is one per day and part
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct Record {
+    pub(crate) day: usize,
+    pub(crate) title: &'static str,
+    // none for a parse error, no part has been run in that case
+    pub(crate) part: Option<String>,
+    // one of ok, error, panic or timeout
+    pub(crate) status: &'static str,
+    pub(crate) answer: Option<Answer>,
+    pub(crate) error: Option<String>,
+    pub(crate) elapsed_us: u64,
+    pub(crate) worker: usize,
+}
+
+pub(crate) fn records(reports: &[Report]) -> Vec<Record> {
+    let mut records = vec![];
+
+    for report in reports {
+        if let Some(err) = &report.error {
+            records.push(Record {
+                day: report.day,
+                title: report.title,
+                part: None,
+                status: Outcome::of_error(err).label(),
+                answer: None,
+                error: Some(err.to_string()),
+                elapsed_us: report.parse_elapsed.as_micros() as u64,
+                worker: report.worker,
+            });
+        }
+
+        for part in &report.parts {
+            let (answer, error) = match &part.answer {
+                Ok(answer) => (Some(answer.clone()), None),
+                Err(err) => (None, Some(err.to_string())),
+            };
+
+            records.push(Record {
+                day: report.day,
+                title: report.title,
+                part: Some(part.part.to_string()),
+                status: Outcome::of(part.answer.as_ref()).label(),
+                answer,
+                error,
+                elapsed_us: part.elapsed.as_micros() as u64,
+                worker: report.worker,
+            });
+        }
+    }
+
+    records
+}
+
+pub(crate) fn to_json(records: &[Record]) -> Result<String, Error> {
+    Ok(serde_json::to_string_pretty(records)?)
+}
+
+// quotes a field only if it has to be, as described in RFC 4180
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+pub(crate) fn to_csv(records: &[Record]) -> String {
+    let mut lines = vec!["day,title,part,status,answer,error,elapsed_us,worker".to_owned()];
+
+    for record in records {
+        let fields = [
+            record.day.to_string(),
+            record.title.to_owned(),
+            record.part.clone().unwrap_or_default(),
+            record.status.to_owned(),
+            record
+                .answer
+                .as_ref()
+                .map(Answer::to_string)
+                .unwrap_or_default(),
+            record.error.clone().unwrap_or_default(),
+            record.elapsed_us.to_string(),
+            record.worker.to_string(),
+        ];
+
+        lines.push(
+            fields
+                .iter()
+                .map(|field| csv_field(field))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv_field, records, to_csv, to_json, Format};
+    use failure::format_err;
+    use problems::runner::{PartReport, Report};
+    use std::time::Duration;
+    use utils::{Answer, Part};
+
+    fn report() -> Report {
+        Report {
+            day: 7,
+            title: "Handy Haversacks",
+            worker: 1,
+            error: None,
+            parse_elapsed: Duration::from_micros(5),
+            parts: vec![
+                PartReport {
+                    part: Part::First,
+                    answer: Ok(Answer::from(337)),
+                    elapsed: Duration::from_micros(10),
+                },
+                PartReport {
+                    part: Part::Second,
+                    answer: Err(format_err!("no bags, \"shiny gold\"")),
+                    elapsed: Duration::from_micros(20),
+                },
+            ],
+            elapsed: Duration::from_micros(35),
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!("json".parse::<Format>().unwrap(), Format::Json);
+        assert_eq!("csv".parse::<Format>().unwrap(), Format::Csv);
+        assert_eq!("text".parse::<Format>().unwrap(), Format::Text);
+        assert!("xml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn test_records() {
+        let mut broken = report();
+        broken.error = Some(format_err!("bad input"));
+        broken.parts.clear();
+
+        let records = records(&[report(), broken]);
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].part.as_deref(), Some("1"));
+        assert_eq!(records[0].answer, Some(Answer::from(337)));
+        assert_eq!(records[0].elapsed_us, 10);
+        assert_eq!(records[1].answer, None);
+        assert_eq!(records[1].error.as_deref(), Some("no bags, \"shiny gold\""));
+        assert_eq!(records[1].status, "error");
+        assert_eq!(records[2].part, None);
+        assert_eq!(records[2].error.as_deref(), Some("bad input"));
+        assert_eq!(records[2].elapsed_us, 5);
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(csv_field("42"), "42");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+
+        assert_eq!(
+            to_csv(&records(&[report()])),
+            "day,title,part,status,answer,error,elapsed_us,worker\n\
+             7,Handy Haversacks,1,ok,337,,10,1\n\
+             7,Handy Haversacks,2,error,,\"no bags, \"\"shiny gold\"\"\",20,1"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let json: serde_json::Value =
+            serde_json::from_str(&to_json(&records(&[report()])).unwrap()).unwrap();
+
+        assert_eq!(json[0]["day"], 7);
+        assert_eq!(json[0]["answer"], 337);
+        assert_eq!(json[0]["error"], serde_json::Value::Null);
+        assert_eq!(json[1]["error"], "no bags, \"shiny gold\"");
+        assert_eq!(json[1]["worker"], 1);
+    }
+}
@@ -0,0 +1,34 @@
+// solvers of every day and the machinery to run them, the `problems` binary is a thin
+// command line front end over this crate
+#[macro_use]
+pub mod problem;
+
+pub mod render;
+pub mod runner;
+
+problems! {
+    problem1::Day1,
+    problem2::Day2,
+    problem3::Day3,
+    problem4::Day4,
+    problem5::Day5,
+    problem6::Day6,
+    problem7::Day7,
+    problem8::Day8,
+    problem9::Day9,
+    problem10::Day10,
+    problem11::Day11,
+    problem12::Day12,
+    problem13::Day13,
+    problem14::Day14,
+    problem15::Day15,
+    problem16::Day16,
+    problem17::Day17,
+    problem18::Day18,
+    problem19::Day19,
+}
+
+// solver of the given day, if it is solved already
+pub fn find(day: usize) -> Option<&'static dyn problem::Solver> {
+    PROBLEMS.iter().copied().find(|solver| solver.day() == day)
+}
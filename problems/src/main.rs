@@ -1,131 +1,393 @@
-#![feature(const_option)]
-
-mod problem1;
-mod problem10;
-mod problem11;
-mod problem12;
-mod problem13;
-mod problem14;
-mod problem15;
-mod problem16;
-mod problem17;
-mod problem18;
-mod problem19;
-mod problem2;
-mod problem3;
-mod problem4;
-mod problem5;
-mod problem6;
-mod problem7;
-mod problem8;
-mod problem9;
+mod bench;
+mod cli;
+mod debugger;
+mod format;
+mod verify;
 
 use colored::*;
-use crossbeam::{queue::SegQueue, thread};
-use failure::Error;
-use std::{marker::Sync, sync::Arc, time::SystemTime};
-
-use utils::RetTypes;
-
-// problems
-use crate::problem1 as p1;
-use crate::problem10 as p10;
-use crate::problem11 as p11;
-use crate::problem12 as p12;
-use crate::problem13 as p13;
-use crate::problem14 as p14;
-use crate::problem15 as p15;
-use crate::problem16 as p16;
-use crate::problem17 as p17;
-use crate::problem18 as p18;
-use crate::problem19 as p19;
-use crate::problem2 as p2;
-use crate::problem3 as p3;
-use crate::problem4 as p4;
-use crate::problem5 as p5;
-use crate::problem6 as p6;
-use crate::problem7 as p7;
-use crate::problem8 as p8;
-use crate::problem9 as p9;
-
-fn exec(f: &(dyn Fn() -> Result<RetTypes, Error>), problem_no: usize) {
-    let now = SystemTime::now();
-    let result = f();
-    let elapsed = now.elapsed().unwrap().as_millis();
-
-    match result {
-        Err(err) => println!(
-            "{} {}:\n{}: {}",
-            "problem".bold(),
-            problem_no.to_string().bold(),
-            "error".bold().red(),
-            err
-        ),
-
-        Ok(answer) => println!(
-            "{} {}:\n{}",
-            "problem".bold(),
-            problem_no.to_string().bold(),
-            answer
-        ),
-    }
-
-    println!("time elapsed for problem: {} millis\n", elapsed);
+use failure::{format_err, Error};
+use std::{
+    env, fs,
+    io::{self, Read},
+    process,
+    time::{Duration, Instant},
+};
+
+use problems::{
+    problem::{Problem, Solver},
+    problem11::{self, Day11},
+    problem17::{self, Day17},
+    problem18::{self, Precedence},
+    problem19::{self, Day19, Grammar},
+    problem8::Day8,
+    render::{self, RenderOptions},
+    runner,
+    runner::Report,
+    PROBLEMS,
+};
+
+use utils::Part;
+
+use crate::format::Format;
+
+fn read_input(path: &str) -> Result<String, Error> {
+    let mut input = String::new();
+
+    if path == "-" {
+        io::stdin().read_to_string(&mut input)?;
+    } else {
+        input = fs::read_to_string(path)
+            .map_err(|err| format_err!("can't read input file '{}': {}", path, err))?;
+    }
+
+    Ok(input)
+}
+
+// day 17 in any number of dimensions, the number of cycles stays the same
+fn run_dimensions(
+    dimensions: usize,
+    custom_input: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<(), Error> {
+    let input = custom_input.unwrap_or(Day17::INPUT);
+    let slice = Day17::parse(input).map_err(|err| err.locate(input).with_day(Day17::DAY))?;
+
+    let now = Instant::now();
+    utils::set_deadline(timeout.map(|timeout| now + timeout));
+    let active = problem17::active_cubes(&slice, dimensions);
+    utils::set_deadline(None);
+
+    println!(
+        "{} {} ({}) in {} dimensions: {}\ntime elapsed for problem: {} millis",
+        "problem".bold(),
+        Day17::DAY.to_string().bold(),
+        Day17::TITLE,
+        dimensions,
+        active?,
+        now.elapsed().as_millis()
+    );
+
+    Ok(())
+}
+
+// draws generations of either day 11 or day 17
+fn run_render(opts: &cli::Options, path: &str, custom_input: Option<&str>) -> Result<(), Error> {
+    let render_opts = RenderOptions {
+        palette: match &opts.palette {
+            Some(palette) => render::Palette::default().merge(palette),
+            None => render::Palette::default(),
+        },
+        cell_size: opts.cell_size.unwrap_or(render::DEFAULT_CELL_SIZE),
+        ..RenderOptions::default()
+    };
+
+    let frames: Vec<_> = if opts.days == [11] {
+        let input = custom_input.unwrap_or(Day11::INPUT);
+        let board = Day11::parse(input).map_err(|err| err.locate(input).with_day(Day11::DAY))?;
+
+        problem11::generations(&board, opts.part.unwrap_or(Part::First))?
+            .iter()
+            .map(|board| render::frame(board, &render_opts))
+            .collect::<Result<_, _>>()?
+    } else {
+        let input = custom_input.unwrap_or(Day17::INPUT);
+        let slice = Day17::parse(input).map_err(|err| err.locate(input).with_day(Day17::DAY))?;
+
+        problem17::z_slices(&slice)?
+            .iter()
+            .map(|board| render::frame(board, &render_opts))
+            .collect::<Result<_, _>>()?
+    };
+
+    render::save(&frames, path, &render_opts)?;
+    println!("{} frames rendered to {}", frames.len(), path);
+
+    Ok(())
+}
+
+// day 18 expression grouped by the precedence and reduced one operation at a time
+fn run_reduce(opts: &cli::Options, expr: &str) -> Result<(), Error> {
+    let precedence = match (&opts.precedence, opts.part) {
+        (Some(precedence), _) => precedence.clone(),
+        (None, Some(Part::Second)) => Precedence::ADDITION_FIRST,
+        (None, _) => Precedence::EQUAL,
+    };
+
+    let tokens = problem18::parse_expr(expr).map_err(|err| err.locate(expr))?;
+    let tree = problem18::build(&tokens, &precedence)?;
+
+    for (idx, step) in tree.reductions()?.iter().enumerate() {
+        let prefix = if idx == 0 { " " } else { "=" };
+        println!("{} {}", prefix, step);
+    }
+
+    Ok(())
+}
+
+// derivations of day 19 messages from rule 0, or where they stop matching
+fn run_explain(
+    opts: &cli::Options,
+    numbers: &[usize],
+    custom_input: Option<&str>,
+) -> Result<(), Error> {
+    let input = custom_input.unwrap_or(Day19::INPUT);
+    let (rules, messages) =
+        Day19::parse(input).map_err(|err| err.locate(input).with_day(Day19::DAY))?;
+
+    let rules = match opts.part {
+        Some(Part::Second) => problem19::with_overrides(&rules, problem19::LOOPED_RULES)?,
+        _ => rules,
+    };
+    let grammar = Grammar::new(&rules, 0)?;
+
+    if let Some(number) = numbers.iter().find(|number| **number > messages.len()) {
+        return Err(format_err!(
+            "no message {}, there are {} of them",
+            number,
+            messages.len()
+        ));
+    }
+
+    for number in numbers {
+        let message = &messages[number - 1];
+        let text = String::from_utf8_lossy(message);
+        println!("{} {}: {}", "message".bold(), number, text);
+
+        match grammar.explain(message) {
+            Ok(tree) => print!("{}", tree),
+            Err(mismatch) => {
+                // the offset is in bytes, messages are ascii anyway
+                println!("{} {}", "no match,".red(), mismatch);
+                println!(
+                    "  {}\n  {:>width$}",
+                    text,
+                    "^",
+                    width = mismatch.matched + 1
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// benchmarks are run one day after another, so they don't compete for cores
+fn run_bench(
+    opts: &cli::Options,
+    iterations: usize,
+    custom_input: Option<&str>,
+) -> Result<bool, Error> {
+    let baseline = opts
+        .baseline
+        .as_deref()
+        .map(|path| {
+            fs::read_to_string(path)
+                .map_err(|err| format_err!("can't read baseline file '{}': {}", path, err))
+                .and_then(|baseline| {
+                    bench::parse_baseline(&baseline)
+                        .map_err(|err| format_err!("broken baseline file '{}', {}", path, err))
+                })
+        })
+        .transpose()?;
+
+    let mut reports = vec![];
+
+    for solver in PROBLEMS {
+        if opts.days.is_empty() || opts.days.contains(&solver.day()) {
+            reports.push(bench::bench(
+                *solver,
+                custom_input.unwrap_or(solver.input()),
+                &runner::selected_parts(*solver, opts.part),
+                iterations,
+                opts.warmup.unwrap_or(bench::DEFAULT_WARMUP),
+            )?);
+        }
+    }
+
+    let ok = bench::print(
+        &reports,
+        baseline.as_ref(),
+        opts.threshold.unwrap_or(bench::DEFAULT_THRESHOLD),
+    );
+
+    if let Some(path) = &opts.save_baseline {
+        fs::write(path, bench::format_baseline(&reports))
+            .map_err(|err| format_err!("can't write baseline file '{}': {}", path, err))?;
+    }
+
+    Ok(ok)
 }
 
 fn main() {
-    println!("\n{}\n\n", "Advent of code 2020".bold());
-
-    #[allow(clippy::type_complexity)]
-    let q: Arc<SegQueue<(&(dyn Fn() -> Result<RetTypes, Error> + Sync), usize)>> =
-        Arc::new(SegQueue::new());
-
-    q.push((&p1::solve, 1));
-    q.push((&p2::solve, 2));
-    q.push((&p3::solve, 3));
-    q.push((&p4::solve, 4));
-    q.push((&p5::solve, 5));
-    q.push((&p6::solve, 6));
-    q.push((&p7::solve, 7));
-    q.push((&p8::solve, 8));
-    q.push((&p9::solve, 9));
-    q.push((&p10::solve, 10));
-    q.push((&p11::solve, 11));
-    q.push((&p12::solve, 12));
-    q.push((&p13::solve, 13));
-    q.push((&p14::solve, 14));
-    q.push((&p15::solve, 15));
-    q.push((&p16::solve, 16));
-    q.push((&p17::solve, 17));
-    q.push((&p18::solve, 18));
-    q.push((&p19::solve, 19));
-
-    println!("{} cores detected\n", num_cpus::get_physical());
-
-    let now = SystemTime::now();
-
-    thread::scope(|s| {
-        for idx in 0..num_cpus::get_physical() {
-            let q = Arc::clone(&q);
-            s.spawn(move |_| {
-                while let Ok((task, task_id)) = q.pop() {
-                    println!("Worker {} executing problem {}\n", idx, task_id);
-                    exec(task, task_id);
-                }
+    let opts = match cli::parse_args(env::args().skip(1)) {
+        Ok(opts) => opts,
+        Err(err) => {
+            eprintln!("{}: {}\n\n{}", "error".bold().red(), err, cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    if opts.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+
+    if opts.list {
+        for solver in PROBLEMS {
+            println!("{:>2}  {}", solver.day(), solver.title());
+        }
+        return;
+    }
+
+    if let Some(day) = opts.days.iter().find(|day| problems::find(**day).is_none()) {
+        eprintln!("{}: day {} is not solved yet", "error".bold().red(), day);
+        process::exit(2);
+    }
+
+    let custom_input = match opts.input.as_deref().map(read_input).transpose() {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("{}: {}", "error".bold().red(), err);
+            process::exit(2);
+        }
+    };
+
+    let answers = if opts.verify {
+        match verify::parse_answers(verify::ANSWERS) {
+            Ok(answers) => Some(answers),
+            Err(err) => {
+                eprintln!("{}: broken answers file, {}", "error".bold().red(), err);
+                process::exit(2);
+            }
+        }
+    } else {
+        None
+    };
+
+    if let Some(iterations) = opts.bench {
+        match run_bench(&opts, iterations, custom_input.as_deref()) {
+            Ok(true) => return,
+            Ok(false) => process::exit(1),
+            Err(err) => {
+                eprintln!("{}: {}", "error".bold().red(), err);
+                process::exit(1);
+            }
+        }
+    }
+
+    if opts.debug {
+        let input = custom_input.as_deref().unwrap_or(Day8::INPUT);
+        let res = Day8::parse(input)
+            .map_err(|err| Error::from(err.locate(input).with_day(Day8::DAY)))
+            .and_then(|program| {
+                let stdin = io::stdin();
+                Ok(debugger::run(&program, stdin.lock(), io::stdout())?)
             });
+
+        if let Err(err) = res {
+            eprintln!("{}: {}", "error".bold().red(), err);
+            process::exit(1);
         }
-    })
-    .unwrap();
+        return;
+    }
 
-    println!(
-        "{} {} {}",
-        "Total time taken:".bold(),
-        now.elapsed()
-            .unwrap()
-            .as_millis()
-            .to_string()
-            .bold()
-            .green(),
-        "millis".bold()
+    if let Some(expr) = &opts.reduce {
+        if let Err(err) = run_reduce(&opts, expr) {
+            eprintln!("{}: {}", "error".bold().red(), err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(numbers) = &opts.explain {
+        if let Err(err) = run_explain(&opts, numbers, custom_input.as_deref()) {
+            eprintln!("{}: {}", "error".bold().red(), err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = &opts.render {
+        if let Err(err) = run_render(&opts, path, custom_input.as_deref()) {
+            eprintln!("{}: {}", "error".bold().red(), err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(dimensions) = opts.dimensions {
+        let timeout = opts.timeout.map(Duration::from_secs_f64);
+        if let Err(err) = run_dimensions(dimensions, custom_input.as_deref(), timeout) {
+            eprintln!("{}: {}", "error".bold().red(), err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    // machine readable formats print nothing but the records
+    let text = opts.format == Format::Text;
+
+    if text {
+        println!("\n{}\n\n", "Advent of code 2020".bold());
+    }
+
+    let solvers: Vec<&dyn Solver> = PROBLEMS
+        .iter()
+        .copied()
+        .filter(|solver| opts.days.is_empty() || opts.days.contains(&solver.day()))
+        .collect();
+
+    if text {
+        println!("{} cores detected\n", num_cpus::get_physical());
+    }
+
+    let now = Instant::now();
+    // in verify mode only the final table is printed
+    let verbose = text && !opts.verify;
+
+    let reports = runner::run_pool(
+        &solvers,
+        custom_input.as_deref(),
+        opts.part,
+        opts.timeout.map(Duration::from_secs_f64),
+        |report| {
+            if verbose {
+                println!("Worker {} executed problem {}\n", report.worker, report.day);
+                runner::print(report);
+            }
+        },
     );
+
+    let all_ok = match &answers {
+        Some(answers) => verify::verify(answers, &reports),
+        None => reports.iter().all(Report::is_ok),
+    };
+
+    match opts.format {
+        Format::Json => match format::to_json(&format::records(&reports)) {
+            Ok(json) => println!("{}", json),
+            Err(err) => {
+                eprintln!("{}: {}", "error".bold().red(), err);
+                process::exit(2);
+            }
+        },
+        Format::Csv => println!("{}", format::to_csv(&format::records(&reports))),
+        Format::Text => {
+            // verify mode has its own summary under the table
+            if answers.is_none() {
+                println!("{} {}", "Summary:".bold(), runner::summary(&reports));
+            }
+
+            println!(
+                "{} {} {}",
+                "Total time taken:".bold(),
+                now.elapsed().as_millis().to_string().bold().green(),
+                "millis".bold()
+            );
+        }
+    }
+
+    if !all_ok {
+        process::exit(1);
+    }
 }
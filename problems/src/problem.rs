@@ -0,0 +1,166 @@
+use std::{any::Any, marker::PhantomData};
+
+use failure::format_err;
+use utils::{Answer, ParseResult, Part, ProblemResult};
+
+pub const PARTS: &[Part] = &[Part::First, Part::Second];
+
+// every day implements this trait, see the `problems!` macro for how to register it
+pub trait Problem {
+    const DAY: usize;
+    const TITLE: &'static str;
+    // puzzle input embedded into the binary, used unless another one is given
+    const INPUT: &'static str;
+
+    type Input: Send + 'static;
+    type Answer1: Into<Answer>;
+    type Answer2: Into<Answer>;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input>;
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1>;
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2>;
+}
+
+// object safe counterpart of `Problem`, so days with different input and answer types
+// can be stored in a single registry
+pub trait Solver: Sync {
+    fn day(&self) -> usize;
+    fn title(&self) -> &'static str;
+    fn input(&self) -> &'static str;
+
+    fn parts(&self) -> &'static [Part] {
+        PARTS
+    }
+
+    fn parse(&self, input_raw: &str) -> ParseResult<Box<dyn Any + Send>>;
+    fn solve(&self, parsed: &(dyn Any + Send), part: Part) -> ProblemResult<Answer>;
+}
+
+pub struct Registered<P>(PhantomData<fn() -> P>);
+
+impl<P> Registered<P> {
+    pub const fn new() -> Self {
+        Registered(PhantomData)
+    }
+}
+
+impl<P> Default for Registered<P> {
+    fn default() -> Self {
+        Registered::new()
+    }
+}
+
+impl<P: Problem> Solver for Registered<P> {
+    fn day(&self) -> usize {
+        P::DAY
+    }
+
+    fn title(&self) -> &'static str {
+        P::TITLE
+    }
+
+    fn input(&self) -> &'static str {
+        P::INPUT
+    }
+
+    fn parse(&self, input_raw: &str) -> ParseResult<Box<dyn Any + Send>> {
+        match P::parse(input_raw) {
+            Ok(input) => Ok(Box::new(input)),
+            Err(err) => Err(err.locate(input_raw).with_day(P::DAY)),
+        }
+    }
+
+    fn solve(&self, parsed: &(dyn Any + Send), part: Part) -> ProblemResult<Answer> {
+        let input = parsed
+            .downcast_ref::<P::Input>()
+            .ok_or_else(|| format_err!("input of day {} has unexpected type", P::DAY))?;
+
+        match part {
+            Part::First => P::part1(input).map(Into::into),
+            Part::Second => P::part2(input).map(Into::into),
+        }
+    }
+}
+
+// declares day modules and puts them into `PROBLEMS` registry, adding a new day
+// is just a matter of adding a `moduleN::DayN` line here
+macro_rules! problems {
+    ($($module:ident::$problem:ident),* $(,)?) => {
+        $(pub mod $module;)*
+
+        pub const PROBLEMS: &[&dyn $crate::problem::Solver] = &[
+            $(&$crate::problem::Registered::<$module::$problem>::new()),*
+        ];
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Problem, Registered, Solver};
+    use failure::format_err;
+    use utils::{parse_token, Answer, ParseResult, Part, ProblemResult};
+
+    struct Sum;
+
+    impl Problem for Sum {
+        const DAY: usize = 42;
+        const TITLE: &'static str = "Sum";
+        const INPUT: &'static str = "1,2,3";
+
+        type Input = Vec<usize>;
+        type Answer1 = usize;
+        type Answer2 = String;
+
+        fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+            input_raw
+                .split(',')
+                .map(|item| parse_token(item, "number"))
+                .collect()
+        }
+
+        fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+            Ok(input.iter().sum())
+        }
+
+        fn part2(_input: &Self::Input) -> ProblemResult<Self::Answer2> {
+            Err(format_err!("no second star"))
+        }
+    }
+
+    #[test]
+    fn test_registered() {
+        let solver: &dyn Solver = &Registered::<Sum>::new();
+
+        assert_eq!(solver.day(), 42);
+        assert_eq!(solver.title(), "Sum");
+
+        let parsed = solver.parse(solver.input()).unwrap();
+        assert_eq!(
+            solver.solve(parsed.as_ref(), Part::First).unwrap(),
+            Answer::Int(6)
+        );
+        assert!(solver.solve(parsed.as_ref(), Part::Second).is_err());
+
+        let err = solver.parse("1,x").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "day 42, line 1, column 3: wrong number 'x'"
+        );
+    }
+
+    #[test]
+    fn test_registry() {
+        let days: Vec<usize> = crate::PROBLEMS.iter().map(|solver| solver.day()).collect();
+        let mut sorted = days.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+
+        assert_eq!(days, sorted);
+
+        assert_eq!(
+            crate::find(8).map(|solver| solver.title()),
+            Some("Handheld Halting")
+        );
+        assert!(crate::find(42).is_none());
+    }
+}
@@ -1,7 +1,9 @@
 use std::collections::HashSet;
 
-use failure::{format_err, Error};
-use utils::{result, split_by_lines, ProblemResult, RetTypes};
+use failure::format_err;
+use utils::{parse_token, split_by_lines, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
 
 const YEAR: usize = 2020;
 
@@ -41,15 +43,26 @@ fn second_star(input: &[usize]) -> ProblemResult<usize> {
     Err(format_err!("solution not found"))
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input: Vec<usize> = split_by_lines(input_raw, &|e: &str| {
-        e.parse::<usize>()
-            .map_err(|_| format_err!("Failed to parse input"))
-    })?;
-
-    Ok(RetTypes::Usize(result(
-        first_star(&input),
-        second_star(&input),
-    )))
+pub struct Day1;
+
+impl Problem for Day1 {
+    const DAY: usize = 1;
+    const TITLE: &'static str = "Report Repair";
+    const INPUT: &'static str = include_str!("./input");
+
+    type Input = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        split_by_lines(input_raw, &|e: &str| parse_token(e, "number"))
+    }
+
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        first_star(input)
+    }
+
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        second_star(input)
+    }
 }
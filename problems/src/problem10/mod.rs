@@ -3,8 +3,10 @@ use std::collections::{HashMap, HashSet};
 
 use itertools::sorted;
 
-use failure::{format_err, Error};
-use utils::{result, split_by_lines, RetTypes};
+use failure::format_err;
+use utils::{parse_token, split_by_lines, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
 
 fn first_star_rec(
     input: &mut HashSet<usize>,
@@ -103,24 +105,39 @@ fn second_star(input: &[usize], target_joltage: usize) -> usize {
     second_star_rec(&mut tmp, &mut HashMap::new(), 0, target_joltage)
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input: Vec<usize> = split_by_lines(input_raw, &|e: &str| {
-        e.parse::<usize>()
-            .map_err(|_| format_err!("Failed to parse input"))
-    })?;
+pub struct Day10;
+
+impl Problem for Day10 {
+    const DAY: usize = 10;
+    const TITLE: &'static str = "Adapter Array";
+    const INPUT: &'static str = include_str!("./input");
 
-    let fst = first_star(&input);
+    type Input = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    Ok(RetTypes::Usize(result(
-        Ok(fst),
-        Ok(second_star(&input, *input.iter().max().unwrap() + 3)),
-    )))
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        split_by_lines(input_raw, &|e: &str| parse_token(e, "number"))
+    }
+
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        Ok(first_star(input))
+    }
+
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        let max = input
+            .iter()
+            .max()
+            .ok_or_else(|| format_err!("there are no adapters"))?;
+
+        Ok(second_star(input, max + 3))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{first_star, second_star};
+
     #[test]
     fn test_first() {
         let res = first_star(&[16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4]);
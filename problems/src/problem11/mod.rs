@@ -1,291 +1,145 @@
-use failure::{format_err, Error};
-use itertools::join;
-use utils::{result, split_by_lines, RetTypes};
+use std::convert::TryFrom;
+
+use utils::{
+    check_deadline, Automaton, CellState, DenseAutomaton, DenseNeighbourhood, Grid, ParseResult,
+    Part, ProblemResult,
+};
+
+use crate::problem::Problem;
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
-enum Cell {
+pub enum Cell {
     Empty,
     Occupied,
     Floor,
 }
 
-#[derive(Copy, Clone, Eq, Debug)]
-enum Dir {
-    N(Cell), // North, ...
-    NE(Cell),
-    E(Cell),
-    SE(Cell),
-    S(Cell),
-    SW(Cell),
-    W(Cell),
-    NW(Cell),
-}
+impl TryFrom<char> for Cell {
+    type Error = ();
 
-impl Dir {
-    fn unwrap(self) -> Cell {
-        match self {
-            Dir::N(c) => c,
-            Dir::NE(c) => c,
-            Dir::E(c) => c,
-            Dir::SE(c) => c,
-            Dir::S(c) => c,
-            Dir::SW(c) => c,
-            Dir::W(c) => c,
-            Dir::NW(c) => c,
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'L' => Ok(Cell::Empty),
+            '#' => Ok(Cell::Occupied),
+            '.' => Ok(Cell::Floor),
+            _ => Err(()),
         }
     }
-
-    fn is_floor(&self) -> bool {
-        self.unwrap() == Cell::Floor
-    }
 }
 
-impl PartialEq for Dir {
-    fn eq(&self, other: &Self) -> bool {
-        match self {
-            Dir::N(_) => matches!(other, Dir::N(_)),
-            Dir::NE(_) => matches!(other, Dir::NE(_)),
-            Dir::E(_) => matches!(other, Dir::E(_)),
-            Dir::SE(_) => matches!(other, Dir::SE(_)),
-            Dir::S(_) => matches!(other, Dir::S(_)),
-            Dir::SW(_) => matches!(other, Dir::SW(_)),
-            Dir::W(_) => matches!(other, Dir::W(_)),
-            Dir::NW(_) => matches!(other, Dir::NW(_)),
+impl From<Cell> for char {
+    fn from(cell: Cell) -> Self {
+        match cell {
+            Cell::Empty => 'L',
+            Cell::Occupied => '#',
+            Cell::Floor => '.',
         }
     }
 }
 
-type ScanFn = dyn Fn(&[Vec<Cell>], usize, usize, usize) -> Vec<Dir>;
-
-fn _print_board(board: &[Vec<Cell>]) {
-    for row in board {
-        println!(
-            "{}",
-            join(
-                row.iter()
-                    .map(|c| match c {
-                        Cell::Empty => 'L',
-                        Cell::Occupied => '#',
-                        Cell::Floor => '.',
-                    })
-                    .collect::<Vec<char>>(),
-                ""
-            )
-        );
-    }
-    println!();
-}
+pub type Board = Grid<Cell>;
 
-fn get_adjecent(board: &[Vec<Cell>], row: usize, col: usize, n: usize) -> Vec<Dir> {
-    let mut res = vec![];
-
-    if n >= board.len() || n >= board[0].len() {
-        return res;
-    }
-
-    // north
-    if row > n - 1 {
-        res.push(Dir::N(board[row - n][col]));
-    }
-
-    // west
-    if col > n - 1 {
-        res.push(Dir::W(board[row][col - n]));
-    }
-
-    // north-west
-    if row > n - 1 && col > n - 1 {
-        res.push(Dir::NW(board[row - n][col - n]));
-    }
-
-    // south
-    if row < board.len() - n {
-        res.push(Dir::S(board[row + n][col]));
-    }
+// an empty seat is taken if there is nobody around, floor is never taken
+const RULES_1: &str = "B0/S0123";
+const RULES_2: &str = "B0/S01234";
 
-    // south-west
-    if col > n - 1 && row < board.len() - n {
-        res.push(Dir::SW(board[row + n][col - n]));
-    }
-
-    // south-east
-    if col < board[0].len() - n && row < board.len() - n {
-        res.push(Dir::SE(board[row + n][col + n]));
-    }
-
-    // east
-    if col < board[0].len() - n {
-        res.push(Dir::E(board[row][col + n]));
-    }
-
-    // north-east
-    if col < board[0].len() - n && row > n - 1 {
-        res.push(Dir::NE(board[row - n][col + n]));
-    }
-
-    res
+fn adjacent(board: &Grid<CellState>, row: usize, col: usize) -> Vec<(usize, usize)> {
+    board.neighbours8(row, col).collect()
 }
 
-fn scan_lines(board: &[Vec<Cell>], row: usize, col: usize, _n: usize) -> Vec<Dir> {
-    let mut n = 1;
-    let mut res = vec![];
-
-    // not very effective in some cases but straightforward
-    while res.len() < 8 {
-        let adj = get_adjecent(board, row, col, n);
-
-        if adj.is_empty() {
-            break;
-        }
-
-        for dir in adj {
-            if dir.is_floor() || res.contains(&dir) {
-                continue;
-            }
-            res.push(dir)
-        }
-
-        n += 1;
-    }
+// the first seat in each direction, floor doesn't block the view
+fn visible(board: &Grid<CellState>, row: usize, col: usize) -> Vec<(usize, usize)> {
+    board
+        .ray_neighbours(row, col, |state| *state == CellState::Void)
+        .collect()
+}
+
+// runs generations until nothing changes, `on_generation` gets the board after each of them,
+// returns the number of occupied seats
+fn simulate<F>(
+    board: &Board,
+    rules: &str,
+    neighbourhood: &DenseNeighbourhood,
+    mut on_generation: F,
+) -> ProblemResult<usize>
+where
+    F: FnMut(&Grid<CellState>),
+{
+    let board = board.map(|cell| match cell {
+        Cell::Empty => CellState::Dead,
+        Cell::Occupied => CellState::Alive,
+        Cell::Floor => CellState::Void,
+    });
+
+    let mut seats = DenseAutomaton::new(board, rules.parse()?, neighbourhood);
+    seats.run_until_stable(|seats| {
+        on_generation(seats.board());
+        Ok(check_deadline()?)
+    })?;
 
-    res
+    Ok(seats.population())
 }
 
-fn check_eq(board_1: &[Vec<Cell>], board_2: &[Vec<Cell>]) -> bool {
-    board_1
-        .iter()
-        .flatten()
-        .zip(board_2.iter().flatten())
-        .try_for_each(|(c1, c2)| -> Result<(), ()> {
-            if c1 != c2 {
-                Err(())
-            } else {
-                Ok(())
-            }
-        })
-        .is_ok()
+fn first_star(board: &Board) -> ProblemResult<usize> {
+    simulate(board, RULES_1, &adjacent, |_| {})
 }
 
-fn clone_board(board: &[Vec<Cell>]) -> Vec<Vec<Cell>> {
-    let mut res = vec![];
-    for row in board {
-        res.push(row.clone());
-    }
-    res
+fn second_star(board: &Board) -> ProblemResult<usize> {
+    simulate(board, RULES_2, &visible, |_| {})
 }
 
-fn update_board(
-    scan_fn: &ScanFn,
-    prev_board: &[Vec<Cell>],
-    cur_board: &mut [Vec<Cell>],
-    occupied_around: usize,
-) {
-    let f = |acc: usize, c: &Dir| {
-        if c.unwrap() == Cell::Occupied {
-            acc + 1
-        } else {
-            acc
-        }
+// the board itself followed by every generation of the given star up to the stable one
+pub fn generations(board: &Board, part: Part) -> ProblemResult<Vec<Board>> {
+    let mut boards = vec![board.clone()];
+
+    let on_generation = |seats: &Grid<CellState>| {
+        boards.push(seats.map(|state| match state {
+            CellState::Dead => Cell::Empty,
+            CellState::Alive => Cell::Occupied,
+            CellState::Void => Cell::Floor,
+        }))
     };
 
-    for row_idx in 0..prev_board.len() {
-        for col_idx in 0..prev_board[row_idx].len() {
-            match prev_board[row_idx][col_idx] {
-                Cell::Empty => {
-                    // no adjecent cells occupied
-                    if scan_fn(&prev_board, row_idx, col_idx, 1)
-                        .iter()
-                        .all(|c| c.unwrap() == Cell::Empty || c.unwrap() == Cell::Floor)
-                    {
-                        cur_board[row_idx][col_idx] = Cell::Occupied;
-                    }
-                }
-                Cell::Occupied => {
-                    // occupied_around or more seats adjacent to it are also occupied, the seat becomes empty
-                    if scan_fn(&prev_board, row_idx, col_idx, 1).iter().fold(0, f)
-                        >= occupied_around
-                    {
-                        cur_board[row_idx][col_idx] = Cell::Empty;
-                    }
-                }
-                _ => {}
-            }
-        }
-    }
-}
+    match part {
+        Part::First => simulate(board, RULES_1, &adjacent, on_generation)?,
+        Part::Second => simulate(board, RULES_2, &visible, on_generation)?,
+    };
 
-fn count_occupied(board: &[Vec<Cell>]) -> usize {
-    board
-        .iter()
-        .flatten()
-        .fold(0, |acc, c| if *c == Cell::Occupied { acc + 1 } else { acc })
+    Ok(boards)
 }
 
-fn first_star(board: &[Vec<Cell>]) -> usize {
-    let mut prev_board = clone_board(board);
-    let mut cur_board = clone_board(board);
-
-    loop {
-        update_board(&get_adjecent, &prev_board, &mut cur_board, 4);
-
-        if check_eq(&prev_board, &cur_board) {
-            break;
-        }
-
-        prev_board = clone_board(&cur_board);
-    }
-
-    count_occupied(&cur_board)
+fn parse(input_raw: &str) -> ParseResult<Board> {
+    Grid::parse(input_raw)
 }
 
-fn second_star(board: &[Vec<Cell>]) -> usize {
-    let mut prev_board = clone_board(board);
-    let mut cur_board = clone_board(board);
+pub struct Day11;
 
-    loop {
-        update_board(&scan_lines, &prev_board, &mut cur_board, 5);
+impl Problem for Day11 {
+    const DAY: usize = 11;
+    const TITLE: &'static str = "Seating System";
+    const INPUT: &'static str = include_str!("./input");
 
-        if check_eq(&prev_board, &cur_board) {
-            break;
-        }
+    type Input = Board;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-        prev_board = clone_board(&cur_board);
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
     }
 
-    count_occupied(&cur_board)
-}
-
-fn parse(input_raw: &str) -> Result<Vec<Vec<Cell>>, Error> {
-    let res: Vec<Vec<Cell>> = split_by_lines(input_raw, &|line: &str| {
-        let mut row = vec![];
-
-        for c in line.chars() {
-            row.push(match c {
-                'L' => Cell::Empty,
-                '.' => Cell::Floor,
-                c => return Err(format_err!("unknown character '{}'", c)),
-            });
-        }
-
-        Ok(row)
-    })?;
-
-    Ok(res)
-}
-
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input = parse(input_raw)?;
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        first_star(input)
+    }
 
-    Ok(RetTypes::Usize(result(
-        Ok(first_star(&input)),
-        Ok(second_star(&input)),
-    )))
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        second_star(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{first_star, parse, second_star};
+    use super::{first_star, generations, parse, second_star};
+    use utils::Part;
 
     const RAW_INPUT: &str = concat!(
         "L.LL.LL.LL\n",
@@ -303,12 +157,25 @@ mod tests {
     #[test]
     fn test_first() {
         let board = parse(RAW_INPUT).unwrap();
-        assert_eq!(first_star(&board), 37);
+        assert_eq!(board.to_string(), RAW_INPUT);
+        assert_eq!(first_star(&board).unwrap(), 37);
     }
 
     #[test]
     fn test_second() {
         let board = parse(RAW_INPUT).unwrap();
-        assert_eq!(second_star(&board), 26);
+        assert_eq!(second_star(&board).unwrap(), 26);
+    }
+
+    #[test]
+    fn test_generations() {
+        let board = parse(RAW_INPUT).unwrap();
+        let boards = generations(&board, Part::First).unwrap();
+
+        // the last generation doesn't change anything
+        assert_eq!(boards.len(), 7);
+        assert_eq!(boards[0], board);
+        assert_eq!(boards[5], boards[6]);
+        assert!(boards[1].to_string().starts_with("#.##.##.##\n"));
     }
 }
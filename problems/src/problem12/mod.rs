@@ -1,8 +1,9 @@
-use failure::{format_err, Error};
-use utils::{result, split_by_lines, RetTypes};
+use utils::{parse_token, split_by_lines, ParseError, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
 
 #[derive(Copy, Clone)]
-enum Op {
+pub enum Op {
     N(isize),
     S(isize),
     E(isize),
@@ -201,10 +202,12 @@ fn second_star(input: &[Op]) -> usize {
     (pt.coords.x.abs() + pt.coords.y.abs()) as usize
 }
 
-fn parse(input_raw: &str) -> Result<Vec<Op>, Error> {
+fn parse(input_raw: &str) -> ParseResult<Vec<Op>> {
     split_by_lines(input_raw, &|line: &str| {
-        let (op, n_str) = (&line[..1], &line[1..]);
-        let n = n_str.parse::<isize>()?;
+        let line = line.trim();
+        let op_len = line.chars().next().map_or(0, char::len_utf8);
+        let (op, n_str) = line.split_at(op_len);
+        let n = parse_token::<isize>(n_str, "number of units")?;
 
         match op {
             "N" => Ok(Op::N(n)),
@@ -214,19 +217,33 @@ fn parse(input_raw: &str) -> Result<Vec<Op>, Error> {
             "L" => Ok(Op::L(n)),
             "R" => Ok(Op::R(n)),
             "F" => Ok(Op::F(n)),
-            _ => Err(format_err!("unknown op '{}'", op)),
+            _ => Err(ParseError::at(op, "unknown op")),
         }
     })
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input = parse(input_raw)?;
+pub struct Day12;
+
+impl Problem for Day12 {
+    const DAY: usize = 12;
+    const TITLE: &'static str = "Rain Risk";
+    const INPUT: &'static str = include_str!("./input");
+
+    type Input = Vec<Op>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
+    }
+
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        Ok(first_star(input))
+    }
 
-    Ok(RetTypes::Usize(result(
-        Ok(first_star(&input)),
-        Ok(second_star(&input)),
-    )))
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        Ok(second_star(input))
+    }
 }
 
 #[cfg(test)]
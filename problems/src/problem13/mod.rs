@@ -1,47 +1,29 @@
-use std::borrow::Cow;
+use std::convert::TryFrom;
 
-use failure::{format_err, Error};
-use utils::{result, RetTypes};
+use failure::format_err;
+use utils::{crt, parse_token, ParseError, ParseResult, ProblemResult};
 
-const BRUTE_FORCE_N: usize = 4;
+use crate::problem::Problem;
 
 #[derive(Debug)]
-struct Sched<'a> {
+pub struct Sched {
     n: usize, // this is required for the first star only
 
-    intervals: Cow<'a, [usize]>,
-    deltas: Cow<'a, [usize]>,
+    intervals: Vec<usize>,
+    // distance from the previous bus, 0 for the first one
+    deltas: Vec<usize>,
 }
 
-impl<'a> Sched<'a> {
-    fn new(intervals: Cow<'a, [usize]>, mut deltas: Cow<'a, [usize]>, n: usize) -> Self {
-        deltas.to_mut().insert(0, 0);
-        Sched {
-            intervals,
-            deltas,
-            n,
-        }
-    }
-
-    fn take_first_n(&self, n: usize) -> Self {
-        Sched::new(
-            Cow::from(self.intervals[..n].to_vec()),
-            Cow::from(self.deltas[1..n].to_vec()),
-            self.n,
-        )
-    }
-
-    fn is_valid(&self, start_offset: usize) -> bool {
-        let mut offset = 0;
-
-        for (offset_idx, int) in self.intervals.iter().enumerate() {
-            offset += self.deltas[offset_idx];
-            if (start_offset + offset) % int != 0 {
-                return false;
-            }
-        }
-
-        true
+impl Sched {
+    // minutes after the first bus every bus should depart at
+    fn offsets(&self) -> Vec<usize> {
+        self.deltas
+            .iter()
+            .scan(0, |offset, delta| {
+                *offset += delta;
+                Some(*offset)
+            })
+            .collect()
     }
 }
 
@@ -55,63 +37,51 @@ fn first_star(sched: &Sched) -> usize {
     res.0 * res.1
 }
 
-fn brute_force(sched: &Sched, start_offset: usize) -> usize {
-    let mut ts = start_offset;
-
-    'outer: loop {
-        ts += 1;
-        if !sched.is_valid(ts) {
-            continue 'outer;
-        }
-
-        return ts - start_offset;
-    }
-}
-
-fn find_period(sched: &Sched) -> (usize, usize) {
-    let offset = brute_force(sched, 0);
-    let period = brute_force(sched, offset);
-
-    (offset, period)
-}
-
-fn second_star(sched: &Sched, brute_force_items: usize) -> usize {
-    let trunc_sched = sched.take_first_n(brute_force_items);
+// every bus with id `b` departing `offset` minutes after the first one means
+// `t ≡ -offset (mod b)`, ids don't have to be coprime
+fn second_star(sched: &Sched) -> ProblemResult<usize> {
+    let congruences: Vec<(i128, i128)> = sched
+        .intervals
+        .iter()
+        .zip(sched.offsets())
+        .map(|(id, offset)| (-(offset as i128), *id as i128))
+        .collect();
 
-    // brute-force repeat period for truncated schedule
-    let (mut cur_offset, period) = find_period(&trunc_sched);
+    let (t, period) = crt(&congruences)
+        .map_err(|err| format_err!("can't solve the schedule, {}", err))?;
 
-    // brute-force the rest
-    loop {
-        if sched.is_valid(cur_offset) {
-            return cur_offset;
-        }
+    // timestamps start from 1, the whole period fits as well as 0
+    let t = if t == 0 { period } else { t };
 
-        cur_offset += period;
-    }
+    usize::try_from(t).map_err(|_| format_err!("timestamp {} is too large", t))
 }
 
-fn parse(input_raw: &str) -> Result<Sched, Error> {
+fn parse(input_raw: &str) -> ParseResult<Sched> {
     let mut splitter = input_raw.lines();
 
-    let n = splitter
-        .next()
-        .ok_or_else(|| format_err!("can't parse timestamp"))?
-        .parse::<usize>()?;
+    let n = parse_token(
+        splitter
+            .next()
+            .ok_or_else(|| ParseError::new("missing timestamp"))?,
+        "timestamp",
+    )?;
 
     let sched_raw = splitter
         .next()
-        .ok_or_else(|| format_err!("can't parse timestamp"))?;
+        .ok_or_else(|| ParseError::new("missing schedule"))?;
 
     let mut intervals: Vec<usize> = vec![];
     let mut deltas: Vec<usize> = vec![];
 
     let mut delta = 0;
 
-    for item in sched_raw.split(',') {
+    for item in sched_raw.trim().split(',') {
         if item != "x" {
             deltas.push(delta);
-            intervals.push(item.parse()?);
+            intervals.push(match parse_token(item, "bus id")? {
+                0 => return Err(ParseError::at(item, "wrong bus id")),
+                id => id,
+            });
 
             delta = 1;
         } else {
@@ -119,83 +89,66 @@ fn parse(input_raw: &str) -> Result<Sched, Error> {
         }
     }
 
+    if intervals.is_empty() {
+        return Err(ParseError::at(sched_raw, "there are no buses in schedule"));
+    }
+
     Ok(Sched {
         n,
-        intervals: Cow::from(intervals),
-        deltas: Cow::from(deltas),
+        intervals,
+        deltas,
     })
 }
 
-#[allow(clippy::unnecessary_wraps)]
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let sched = parse(input_raw)?;
+pub struct Day13;
 
-    Ok(RetTypes::Usize(result(
-        Ok(first_star(&sched)),
-        Ok(second_star(&sched, BRUTE_FORCE_N)),
-    )))
-}
+impl Problem for Day13 {
+    const DAY: usize = 13;
+    const TITLE: &'static str = "Shuttle Search";
+    const INPUT: &'static str = include_str!("./input");
 
-#[cfg(test)]
-mod tests {
-    use std::borrow::Cow;
+    type Input = Sched;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    use super::{brute_force, find_period, second_star, Sched};
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
+    }
 
-    #[test]
-    fn test_bf() {
-        assert_eq!(
-            brute_force(&Sched::new(Cow::from(vec![5, 7]), Cow::from(vec![1]), 0), 0),
-            20
-        );
-        assert_eq!(
-            brute_force(
-                &Sched::new(Cow::from(vec![67, 7, 59, 61]), Cow::from(vec![1, 1, 1]), 0),
-                0
-            ),
-            754018
-        );
-        assert_eq!(
-            brute_force(
-                &Sched::new(Cow::from(vec![67, 7, 59, 61]), Cow::from(vec![2, 1, 1]), 0),
-                0
-            ),
-            779210
-        );
-        assert_eq!(
-            brute_force(
-                &Sched::new(
-                    Cow::from(vec![1789, 37, 47, 1889]),
-                    Cow::from(vec![1, 1, 1]),
-                    0
-                ),
-                0
-            ),
-            1202161486
-        );
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        Ok(first_star(input))
+    }
+
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        second_star(input)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{first_star, parse, second_star};
 
     #[test]
-    fn text_find_period() {
-        assert_eq!(
-            find_period(&Sched::new(Cow::from(vec![3, 5]), Cow::from(vec![1]), 0)),
-            (9, 15)
-        );
+    fn test_first_star() {
+        let sched = parse("939\n7,13,x,x,59,x,31,19").unwrap();
+        assert_eq!(first_star(&sched), 295);
     }
 
     #[test]
     fn test_second_star() {
+        let second = |input: &str| second_star(&parse(input).unwrap());
+
+        assert_eq!(second("0\n7,13,x,x,59,x,31,19").unwrap(), 1068781);
+        assert_eq!(second("0\n67,7,59,61").unwrap(), 754018);
+        assert_eq!(second("0\n67,x,7,59,61").unwrap(), 779210);
+        assert_eq!(second("0\n1789,37,47,1889").unwrap(), 1202161486);
+        assert_eq!(second("0\n7").unwrap(), 7);
+
+        // ids with common factors
+        assert_eq!(second("0\n4,x,6").unwrap(), 4);
         assert_eq!(
-            second_star(
-                &Sched::new(
-                    Cow::from(vec![1789, 37, 47, 1889]),
-                    Cow::from(vec![1, 1, 1]),
-                    3,
-                ),
-                2,
-            ),
-            1202161486
+            second("0\n4,6").unwrap_err().to_string(),
+            "can't solve the schedule, no solution, x = 0 (mod 4) contradicts x = 5 (mod 6)"
         );
     }
 }
@@ -1,57 +1,65 @@
 use std::collections::HashMap;
 
-use failure::{format_err, Error};
-use utils::{result, RetTypes};
+use utils::{parse_token, ParseError, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
 
 const LEAST_SIGNIFICANT_ZERO: usize = (1 << 35) - 2;
 
 #[derive(Debug, PartialEq)]
-enum MaskBit {
+pub enum MaskBit {
     One,
     Zero,
     None,
 }
 
 #[derive(Debug)]
-enum Instr {
+pub enum Instr {
     Write(usize, usize),
     Mask(Vec<MaskBit>),
 }
 
-fn parse(input_raw: &str) -> Result<Vec<Instr>, Error> {
+const MASK_LEN: usize = 36;
+
+fn parse(input_raw: &str) -> ParseResult<Vec<Instr>> {
     let mut prog = Vec::new();
 
-    for line in input_raw.lines() {
-        let tmp = line.replace(' ', "");
-        if tmp.starts_with("mask") {
-            let mut splitter = tmp.split('=');
-            splitter.next();
-            let bitmask: Result<Vec<MaskBit>, Error> = splitter
-                .next()
-                .ok_or_else(|| format_err!("error parsing bitmask"))?
-                .chars()
-                .map(|s| match s {
+    for line in input_raw.lines().filter(|line| !line.trim().is_empty()) {
+        let (cmd, value) = line
+            .split_once('=')
+            .ok_or_else(|| ParseError::at(line, "expected '<command> = <value>', got"))?;
+        let (cmd, value) = (cmd.trim(), value.trim());
+
+        if cmd == "mask" {
+            if value.len() != MASK_LEN {
+                return Err(ParseError::at(
+                    value,
+                    format!("expected {} bits long mask, got", MASK_LEN),
+                ));
+            }
+
+            let bitmask: ParseResult<Vec<MaskBit>> = value
+                .char_indices()
+                .map(|(idx, s)| match s {
                     '0' => Ok(MaskBit::Zero),
                     '1' => Ok(MaskBit::One),
                     'X' => Ok(MaskBit::None),
-                    _ => Err(format_err!("unknown symbol")),
+                    _ => Err(ParseError::at(&value[idx..idx + s.len_utf8()], "wrong mask bit")),
                 })
                 .rev()
                 .collect();
             prog.push(Instr::Mask(bitmask?));
-        } else if tmp.starts_with("mem") {
-            let mut splitter = tmp.split('=');
-            let addr = splitter
-                .next()
-                .unwrap()
-                .replace('[', "")
-                .replace(']', "")
-                .replace("mem", "")
-                .parse::<usize>()?;
-            let value = splitter.next().unwrap().parse::<usize>()?;
+        } else if let Some(addr) = cmd.strip_prefix("mem[").and_then(|s| s.strip_suffix(']')) {
+            // both stars apply the current mask to every write
+            if prog.is_empty() {
+                return Err(ParseError::at(cmd, "memory is written before any mask is set"));
+            }
+
+            let addr = parse_token(addr, "address")?;
+            let value = parse_token(value, "value")?;
             prog.push(Instr::Write(addr, value))
         } else {
-            return Err(format_err!("unexpected command"));
+            return Err(ParseError::at(cmd, "unknown command"));
         }
     }
 
@@ -122,7 +130,7 @@ fn second_star(prog: &[Instr]) -> usize {
                 }
 
                 // apply floating bits rules
-                let addrs = gen_floating_addrs(addr, &cur_bitmask.unwrap(), 0);
+                let addrs = gen_floating_addrs(addr, cur_bitmask.unwrap(), 0);
                 for addr in addrs {
                     mem.insert(addr, *value);
                 }
@@ -133,13 +141,26 @@ fn second_star(prog: &[Instr]) -> usize {
     mem.values().sum()
 }
 
-#[allow(clippy::unnecessary_wraps)]
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let prog = parse(input_raw)?;
+pub struct Day14;
+
+impl Problem for Day14 {
+    const DAY: usize = 14;
+    const TITLE: &'static str = "Docking Data";
+    const INPUT: &'static str = include_str!("./input");
 
-    Ok(RetTypes::Usize(result(
-        Ok(first_star(&prog)),
-        Ok(second_star(&prog)),
-    )))
+    type Input = Vec<Instr>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
+    }
+
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        Ok(first_star(input))
+    }
+
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        Ok(second_star(input))
+    }
 }
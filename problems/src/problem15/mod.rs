@@ -1,13 +1,17 @@
 use std::collections::HashMap;
 use std::hash::BuildHasherDefault;
 
-use failure::Error;
 use fxhash::{FxBuildHasher, FxHasher};
 
-use utils::{result, RetTypes};
+use utils::{check_deadline, parse_token, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
 
 type SeqIndex = usize;
 
+// the game loop checks the deadline once per this many turns
+const CHECK_EVERY: usize = 1 << 20;
+
 fn find_last_number(seq: &HashMap<usize, Vec<SeqIndex>, BuildHasherDefault<FxHasher>>) -> usize {
     let tmp = seq
         .iter()
@@ -19,12 +23,16 @@ fn find_last_number(seq: &HashMap<usize, Vec<SeqIndex>, BuildHasherDefault<FxHas
 fn find_last(
     seq: &mut HashMap<usize, Vec<SeqIndex>, BuildHasherDefault<FxHasher>>,
     scan_until: usize,
-) -> usize {
+) -> ProblemResult<usize> {
     let mut last_num = find_last_number(seq);
 
     assert!(scan_until > seq.len());
 
-    for _ in 0..scan_until - seq.len() {
+    for iter in 0..scan_until - seq.len() {
+        if iter % CHECK_EVERY == 0 {
+            check_deadline()?;
+        }
+
         let indices = &seq[&last_num];
         let l = indices.len();
 
@@ -38,31 +46,34 @@ fn find_last(
             last_num = last_idx - indices[l - 2];
         }
 
-        seq.entry(last_num)
-            .or_insert_with(Vec::new)
-            .push(last_idx + 1);
+        seq.entry(last_num).or_default().push(last_idx + 1);
     }
 
-    last_num
+    Ok(last_num)
 }
 
-fn first_star(seq: &mut HashMap<usize, Vec<SeqIndex>, BuildHasherDefault<FxHasher>>) -> usize {
+fn first_star(
+    seq: &mut HashMap<usize, Vec<SeqIndex>, BuildHasherDefault<FxHasher>>,
+) -> ProblemResult<usize> {
     find_last(seq, 2020)
 }
 
-fn second_star(seq: &mut HashMap<usize, Vec<SeqIndex>, BuildHasherDefault<FxHasher>>) -> usize {
+fn second_star(
+    seq: &mut HashMap<usize, Vec<SeqIndex>, BuildHasherDefault<FxHasher>>,
+) -> ProblemResult<usize> {
     find_last(seq, 30000000)
 }
 
 fn parse(
     input_raw: &str,
-) -> Result<HashMap<usize, Vec<SeqIndex>, BuildHasherDefault<FxHasher>>, Error> {
+) -> ParseResult<HashMap<usize, Vec<SeqIndex>, BuildHasherDefault<FxHasher>>> {
     let mut res = HashMap::with_hasher(FxBuildHasher::default());
     input_raw
+        .trim()
         .split(',')
         .enumerate()
-        .try_for_each::<_, Result<(), Error>>(|(idx, item)| {
-            let n = item.parse::<usize>()?;
+        .try_for_each(|(idx, item)| -> ParseResult<()> {
+            let n = parse_token(item, "number")?;
             res.entry(n).or_insert_with(Vec::new).push(idx + 1);
             Ok(())
         })?;
@@ -70,13 +81,26 @@ fn parse(
     Ok(res)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let mut start_seq = parse(input_raw)?;
+pub struct Day15;
+
+impl Problem for Day15 {
+    const DAY: usize = 15;
+    const TITLE: &'static str = "Rambunctious Recitation";
+    const INPUT: &'static str = include_str!("./input");
 
-    Ok(RetTypes::Usize(result(
-        Ok(first_star(&mut start_seq.clone())),
-        Ok(second_star(&mut start_seq)),
-    )))
+    type Input = HashMap<usize, Vec<SeqIndex>, BuildHasherDefault<FxHasher>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
+    }
+
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        first_star(&mut input.clone())
+    }
+
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        second_star(&mut input.clone())
+    }
 }
@@ -1,9 +1,11 @@
 use std::collections::{HashMap, HashSet};
 
-use failure::{format_err, Error};
-use utils::{result, RetTypes};
+use failure::format_err;
+use utils::{check_deadline, parse_token, ParseError, ParseResult, ProblemResult};
 
-#[derive(Debug)]
+use crate::problem::Problem;
+
+#[derive(Debug, Clone)]
 struct Intervals {
     intervals: Vec<(usize, usize)>,
 }
@@ -13,17 +15,17 @@ impl Intervals {
         Self { intervals: vec![] }
     }
 
-    fn add_interval(&mut self, from: usize, to: usize) -> Result<&mut Self, Error> {
+    // returns false if the interval intersects one of already added, intersections are prohibited
+    fn add_interval(&mut self, from: usize, to: usize) -> bool {
         for int in self.intervals.iter() {
             if (from >= int.0 && from <= int.1) || (to >= int.0 && to <= int.1) {
-                // intersections are prohibited
-                return Err(format_err!("intervals intersection"));
+                return false;
             }
         }
 
         self.intervals.push((from, to));
 
-        Ok(self)
+        true
     }
 
     fn is_inside_interval(&self, val: usize) -> bool {
@@ -35,8 +37,8 @@ impl Intervals {
         false
     }
 }
-#[derive(Debug)]
-struct Tickets {
+#[derive(Debug, Clone)]
+pub struct Tickets {
     fields_ranges: HashMap<String, Intervals>,
     fields_order: Vec<String>,
     my_ticket: Vec<usize>,
@@ -82,17 +84,28 @@ fn first_star(tickets: &Tickets) -> (usize, HashSet<usize>) {
     (net, to_remove)
 }
 
-fn second_star(tickets: &Tickets) -> usize {
+fn second_star(tickets: &Tickets) -> ProblemResult<usize> {
     let mut fields_map = HashMap::new();
     let mut value_idx = 0;
+    // a whole pass over the values which resolves nothing would be repeated forever
+    let mut resolved = false;
 
     let mut fields_order = tickets.fields_order.clone();
 
     // for a given field label find appropriate set of values
     while fields_map.len() < tickets.fields_ranges.len() {
+        check_deadline()?;
+
         // start all over
         if value_idx >= tickets.fields_ranges.len() {
+            if !resolved {
+                return Err(format_err!(
+                    "can't tell fields {} apart",
+                    fields_order.join(", ")
+                ));
+            }
             value_idx = 0;
+            resolved = false;
         }
 
         let mut matched = 0;
@@ -122,6 +135,7 @@ fn second_star(tickets: &Tickets) -> usize {
         if matched == 1 {
             fields_map.insert(found_label, value_idx);
             fields_order.remove(remove_idx);
+            resolved = true;
         }
 
         value_idx += 1;
@@ -134,7 +148,7 @@ fn second_star(tickets: &Tickets) -> usize {
         }
     }
 
-    prod
+    Ok(prod)
 }
 
 enum ParseState {
@@ -143,12 +157,29 @@ enum ParseState {
     NearbyTickets,
 }
 
-fn parse(input_raw: &str) -> Result<Tickets, Error> {
+fn parse_ticket(line: &str, fields: usize) -> ParseResult<Vec<usize>> {
+    let ticket = line
+        .trim()
+        .split(',')
+        .map(|x| parse_token(x, "ticket value"))
+        .collect::<ParseResult<Vec<usize>>>()?;
+
+    if ticket.len() != fields {
+        return Err(ParseError::at(
+            line,
+            format!("expected {} values in ticket, got", fields),
+        ));
+    }
+
+    Ok(ticket)
+}
+
+fn parse(input_raw: &str) -> ParseResult<Tickets> {
     let mut state = ParseState::FieldsIntervals;
     let mut tickets = Tickets::new();
 
     for line in input_raw.lines() {
-        if line.is_empty() {
+        if line.trim().is_empty() {
             continue;
         }
 
@@ -158,20 +189,29 @@ fn parse(input_raw: &str) -> Result<Tickets, Error> {
                     state = ParseState::MyTicket;
                     continue;
                 }
-                let mut splitter = line.trim().split(':');
-                let label = splitter.next().unwrap();
-                let values = splitter.next().unwrap();
-
-                for interval in values.replace(' ', "").split("or") {
-                    let mut splitter = interval.split('-');
-                    let from = splitter.next().unwrap().parse::<usize>()?;
-                    let to = splitter.next().unwrap().parse::<usize>()?;
-
-                    tickets
+                let (label, values) = line.trim().split_once(':').ok_or_else(|| {
+                    ParseError::at(line, "expected '<field>: <from>-<to> or <from>-<to>', got")
+                })?;
+
+                for interval in values.split(" or ") {
+                    let (from, to) = interval
+                        .split_once('-')
+                        .ok_or_else(|| ParseError::at(interval, "expected '<from>-<to>', got"))?;
+                    let from = parse_token(from, "interval start")?;
+                    let to = parse_token(to, "interval end")?;
+
+                    let added = tickets
                         .fields_ranges
                         .entry(label.to_owned())
                         .or_insert_with(Intervals::new)
-                        .add_interval(from, to)?;
+                        .add_interval(from, to);
+
+                    if !added {
+                        return Err(ParseError::at(
+                            interval.trim(),
+                            "interval intersects another one of the same field",
+                        ));
+                    }
                 }
 
                 tickets.fields_order.push(label.to_owned());
@@ -181,45 +221,117 @@ fn parse(input_raw: &str) -> Result<Tickets, Error> {
                     state = ParseState::NearbyTickets;
                     continue;
                 }
-                tickets.my_ticket = line
-                    .trim()
-                    .split(',')
-                    .map(|x| x.parse::<usize>().unwrap())
-                    .collect();
+                tickets.my_ticket = parse_ticket(line, tickets.fields_order.len())?;
             }
             ParseState::NearbyTickets => {
-                tickets.nearby_tickets.push(
-                    line.trim()
-                        .split(',')
-                        .map(|x| x.parse::<usize>().unwrap())
-                        .collect(),
-                );
+                let ticket = parse_ticket(line, tickets.fields_order.len())?;
+                tickets.nearby_tickets.push(ticket);
             }
         }
     }
 
+    if tickets.my_ticket.is_empty() {
+        return Err(ParseError::new("missing 'your ticket:' section"));
+    }
+
     Ok(tickets)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let mut tickets = parse(input_raw)?;
+pub struct Day16;
 
-    let (err_rate, nearby_tickets_to_remove) = first_star(&tickets);
-    let mut new_nearby_tickets = vec![];
+impl Problem for Day16 {
+    const DAY: usize = 16;
+    const TITLE: &'static str = "Ticket Translation";
+    const INPUT: &'static str = include_str!("./input");
 
-    // leave only valid tickets
-    for (idx, ticket) in tickets.nearby_tickets.iter().enumerate() {
-        if !nearby_tickets_to_remove.contains(&idx) {
-            new_nearby_tickets.push(ticket.clone());
-        }
+    type Input = Tickets;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
+    }
+
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        Ok(first_star(input).0)
     }
 
-    tickets.nearby_tickets = new_nearby_tickets;
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        let (_, nearby_tickets_to_remove) = first_star(input);
+        let mut tickets = input.clone();
 
-    Ok(RetTypes::Usize(result(
-        Ok(err_rate),
-        Ok(second_star(&tickets)),
-    )))
+        // leave only valid tickets
+        tickets.nearby_tickets = input
+            .nearby_tickets
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !nearby_tickets_to_remove.contains(idx))
+            .map(|(_, ticket)| ticket.clone())
+            .collect();
+
+        second_star(&tickets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{first_star, parse, second_star};
+
+    const INPUT_RAW: &str = "class: 1-3 or 5-7
+row: 6-11 or 33-44
+seat: 13-40 or 45-50
+
+your ticket:
+7,1,14
+
+nearby tickets:
+7,3,47
+40,4,50
+55,2,20
+38,6,12";
+
+    #[test]
+    fn test_first() {
+        let tickets = parse(INPUT_RAW).unwrap();
+        assert_eq!(first_star(&tickets).0, 71);
+    }
+
+    #[test]
+    fn test_second() {
+        let input = "departure class: 0-1 or 4-19
+row: 0-5 or 8-19
+departure seat: 0-13 or 16-19
+
+your ticket:
+11,12,13
+
+nearby tickets:
+3,9,18
+15,1,5
+5,14,9";
+        assert_eq!(second_star(&parse(input).unwrap()).unwrap(), 12 * 13);
+
+        // both fields fit both values
+        let input = "a: 1-5 or 7-9\nb: 1-5 or 7-9\n\nyour ticket:\n1,2\n\nnearby tickets:\n3,4";
+        assert_eq!(
+            second_star(&parse(input).unwrap()).unwrap_err().to_string(),
+            "can't tell fields a, b apart"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = INPUT_RAW.replace("55,2,20", "55,x,20");
+        let err = parse(&input).unwrap_err().locate(&input);
+        assert_eq!((err.line, err.column), (Some(11), Some(4)));
+        assert_eq!(err.to_string(), "line 11, column 4: wrong ticket value 'x'");
+
+        let input = INPUT_RAW.replace("55,2,20", "55,2");
+        assert!(parse(&input).is_err());
+
+        let input = INPUT_RAW.replace("1-3 or 5-7", "1-3 or 2-7");
+        assert!(parse(&input).is_err());
+
+        assert!(parse("class: 1-3 or 5-7").is_err());
+    }
 }
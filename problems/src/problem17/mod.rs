@@ -1,125 +1,171 @@
-use std::collections::HashSet;
-use std::fmt::Debug;
-use std::hash::Hash;
+use failure::format_err;
+use utils::{check_deadline, Automaton, Grid, ParseResult, ProblemResult, SparseAutomaton};
 
-use failure::Error;
-use utils::{result, RetTypes};
+use crate::problem::Problem;
 
 mod point_trait;
-use point_trait::Point;
-
-mod point3d;
-use point3d::Point3D;
-
-mod point4d;
-use point4d::Point4D;
-
-fn count_active<T>(coords: T, univere: &HashSet<T>) -> usize
-where
-    T: Point + Eq + Hash + Copy,
-{
-    coords.get_vicinity().iter().fold(0, |acc, point| {
-        if univere.contains(point) {
-            acc + 1
-        } else {
-            acc
-        }
-    })
-}
+pub use point_trait::Point;
 
-fn engine<T>(initial_cubes: &[T]) -> HashSet<T>
-where
-    T: Point + Eq + Hash + Copy + Debug,
-{
-    let mut cur_universe: HashSet<T> = HashSet::new();
+mod point_n;
+pub use point_n::PointN;
 
-    for cube in initial_cubes {
-        cur_universe.insert(*cube);
-    }
+mod symmetric;
+pub use symmetric::SymmetricCubes;
 
-    for _ in 0..6 {
-        let mut next_universe: HashSet<T> = HashSet::new();
+const RULES: &str = "B3/S23";
+const CYCLES: usize = 6;
 
-        let mut visited: HashSet<T> = HashSet::new();
-        let mut to_visit: Vec<T> = vec![];
+// the highest number of dimensions `active_cubes` supports
+pub const MAX_DIMENSIONS: usize = 10;
 
-        to_visit.extend(cur_universe.iter());
+// active cubes of the initial slice
+pub type Slice = Vec<PointN<2>>;
 
-        while !to_visit.is_empty() {
-            // unwrap with ease, because it has been checked above in while-loop body
-            let coords = to_visit.pop().unwrap();
+// number of active cubes after the boot process in the given number of dimensions
+pub fn active_cubes(slice: &[PointN<2>], dimensions: usize) -> ProblemResult<usize> {
+    if !(2..=MAX_DIMENSIONS).contains(&dimensions) {
+        return Err(format_err!(
+            "number of dimensions must be from 2 to {}",
+            MAX_DIMENSIONS
+        ));
+    }
 
-            // get vicinity coords for only those volumes which aren't empty
-            if cur_universe.contains(&coords) {
-                let vicinity = coords.get_vicinity();
+    let mut cubes = SymmetricCubes::new(slice, dimensions, RULES.parse()?);
+    cubes.run(CYCLES, |_| Ok(check_deadline()?))?;
 
-                // extend, ignoring already visited points
-                for point in vicinity.iter() {
-                    if !visited.contains(&point) {
-                        to_visit.push(*point);
+    Ok(cubes.population())
+}
+
+// gap between z-slices in `z_slices`
+const GAP: char = ' ';
+
+// every generation of the 3D boot process, from the initial one on, as a single board
+// of z-slices from -CYCLES to CYCLES side by side, all of them of the same size
+pub fn z_slices(slice: &[PointN<2>]) -> ProblemResult<Vec<Grid<char>>> {
+    let mut cubes = SparseAutomaton::new(
+        slice.iter().map(PointN::convert::<3>),
+        RULES.parse()?,
+        &PointN::<3>::get_vicinity,
+    );
+
+    let mut generations = vec![cubes.alive().clone()];
+    cubes.run(CYCLES, |cubes| {
+        generations.push(cubes.alive().clone());
+        Ok(check_deadline()?)
+    })?;
+
+    // bounds of x and y over every generation
+    let points = || generations.iter().flatten();
+    let bound = |axis: usize| {
+        let coords = points().map(move |p: &PointN<3>| p.coords[axis]);
+        (coords.clone().min().unwrap_or(0), coords.max().unwrap_or(0))
+    };
+    let ((min_x, max_x), (min_y, max_y)) = (bound(0), bound(1));
+
+    let (width, height) = ((max_x - min_x + 1) as usize, (max_y - min_y + 1) as usize);
+    let depth = CYCLES as isize;
+    let slices = 2 * CYCLES + 1;
+
+    Ok(generations
+        .iter()
+        .map(|alive| {
+            let mut board = Grid::filled(slices * (width + 1) - 1, height, GAP);
+
+            for z in -depth..=depth {
+                let left = (z + depth) as usize * (width + 1);
+
+                for row in 0..height {
+                    for col in 0..width {
+                        let p = PointN::new([min_x + col as isize, min_y + row as isize, z]);
+                        board[(row, left + col)] = if alive.contains(&p) { '#' } else { '.' };
                     }
                 }
+            }
+
+            board
+        })
+        .collect())
+}
 
-                let num_active = count_active(coords, &cur_universe);
+fn first_star(slice: &[PointN<2>]) -> ProblemResult<usize> {
+    active_cubes(slice, 3)
+}
 
-                // cube remains active
-                if num_active == 2 || num_active == 3 {
-                    next_universe.insert(coords);
-                }
-            } else {
-                // empty volume case
-                let num_active = count_active(coords, &cur_universe);
+fn second_star(slice: &[PointN<2>]) -> ProblemResult<usize> {
+    active_cubes(slice, 4)
+}
 
-                // new cube appears
-                if num_active == 3 {
-                    next_universe.insert(coords);
-                }
-            }
+fn parse(input_raw: &str) -> ParseResult<Slice> {
+    let slice = Grid::parse_with(input_raw, |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })?;
+
+    Ok(slice
+        .positions()
+        .filter(|pos| slice[*pos])
+        .map(|(row, col)| PointN::new([col as isize, row as isize]))
+        .collect())
+}
 
-            visited.insert(coords);
-        }
+pub struct Day17;
 
-        // swap universe
-        cur_universe = next_universe;
+impl Problem for Day17 {
+    const DAY: usize = 17;
+    const TITLE: &'static str = "Conway Cubes";
+    const INPUT: &'static str = include_str!("./input");
+
+    type Input = Slice;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
     }
 
-    cur_universe
-}
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        first_star(input)
+    }
 
-fn first_star(initial_cubes: &[Point3D]) -> usize {
-    let universe = engine(&initial_cubes);
-    universe.len()
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        second_star(input)
+    }
 }
 
-fn second_star(initial_cubes: &[Point3D]) -> usize {
-    let initial_cubes_4d: Vec<Point4D> = initial_cubes.iter().cloned().map(Point4D::from).collect();
-    let universe = engine(&initial_cubes_4d);
-    universe.len()
-}
+#[cfg(test)]
+mod tests {
+    use super::{active_cubes, first_star, parse, z_slices};
 
-fn parse(input_raw: &str) -> Vec<Point3D> {
-    let mut res = Vec::new();
-    for (row_idx, line) in input_raw.lines().enumerate() {
-        for (col_idx, c) in line.chars().enumerate() {
-            if c == '#' {
-                res.push(Point3D {
-                    x: col_idx as isize,
-                    y: row_idx as isize,
-                    z: 0,
-                })
-            }
-        }
+    #[test]
+    fn test_first() {
+        let cubes = parse(".#.\n..#\n###").unwrap();
+        assert_eq!(cubes.len(), 5);
+        assert_eq!(first_star(&cubes).unwrap(), 112);
+
+        assert!(parse(".#.\n..x").is_err());
     }
-    res
-}
 
-#[allow(clippy::unnecessary_wraps)]
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let cubes = parse(input_raw);
+    #[test]
+    fn test_dimensions() {
+        let cubes = parse(".#.\n..#\n###").unwrap();
+        assert_eq!(active_cubes(&cubes, 3).unwrap(), 112);
+        assert!(active_cubes(&cubes, 1).is_err());
+        assert_eq!(active_cubes(&cubes, 4).unwrap(), 848);
+        assert!(active_cubes(&cubes, 11).is_err());
+    }
+
+    #[test]
+    fn test_z_slices() {
+        let cubes = parse(".#.\n..#\n###").unwrap();
+        let boards = z_slices(&cubes).unwrap();
+
+        assert_eq!(boards.len(), 7);
+        assert!(boards.iter().all(|board| board.width() == boards[0].width()));
 
-    Ok(RetTypes::Usize(result(
-        Ok(first_star(&cubes)),
-        Ok(second_star(&cubes)),
-    )))
+        let active = |board: &utils::Grid<char>| board.iter().filter(|c| **c == '#').count();
+        assert_eq!(active(&boards[0]), 5);
+        assert_eq!(active(&boards[1]), 11);
+        assert_eq!(active(&boards[6]), 112);
+    }
 }
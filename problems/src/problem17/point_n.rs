@@ -0,0 +1,139 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::Point;
+
+// point of the integer lattice in D dimensions
+#[derive(Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct PointN<const D: usize> {
+    pub coords: [isize; D],
+}
+
+impl<const D: usize> PointN<D> {
+    pub fn new(coords: [isize; D]) -> Self {
+        PointN { coords }
+    }
+
+    pub fn origin() -> Self {
+        PointN { coords: [0; D] }
+    }
+
+    // the same point in E dimensions, extra coordinates are zeros and missing ones are dropped
+    pub fn convert<const E: usize>(&self) -> PointN<E> {
+        let mut coords = [0; E];
+        for (to, from) in coords.iter_mut().zip(self.coords.iter()) {
+            *to = *from;
+        }
+
+        PointN { coords }
+    }
+}
+
+impl<const D: usize> Default for PointN<D> {
+    fn default() -> Self {
+        PointN::origin()
+    }
+}
+
+impl<const D: usize> From<[isize; D]> for PointN<D> {
+    fn from(coords: [isize; D]) -> Self {
+        PointN { coords }
+    }
+}
+
+impl<const D: usize> Point for PointN<D> {
+    // every combination of -1, 0 and 1 offsets but all zeros, 3^D - 1 of them
+    fn get_vicinity(&self) -> Vec<Self> {
+        let total = 3usize.pow(D as u32);
+        let mut res = Vec::with_capacity(total - 1);
+
+        for code in 0..total {
+            let mut point = *self;
+            let mut rest = code;
+
+            for coord in point.coords.iter_mut() {
+                *coord += (rest % 3) as isize - 1;
+                rest /= 3;
+            }
+
+            if point != *self {
+                res.push(point);
+            }
+        }
+
+        res
+    }
+}
+
+impl<const D: usize> Add for PointN<D> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        for (a, b) in self.coords.iter_mut().zip(other.coords.iter()) {
+            *a += b;
+        }
+        self
+    }
+}
+
+impl<const D: usize> Sub for PointN<D> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl<const D: usize> Neg for PointN<D> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self * -1
+    }
+}
+
+impl<const D: usize> Mul<isize> for PointN<D> {
+    type Output = Self;
+
+    fn mul(mut self, k: isize) -> Self {
+        for a in self.coords.iter_mut() {
+            *a *= k;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Point, PointN};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_vicinity() {
+        let p = PointN::new([1, -2, 3]);
+        let vicinity: HashSet<_> = p.get_vicinity().into_iter().collect();
+
+        assert_eq!(vicinity.len(), 26);
+        assert!(!vicinity.contains(&p));
+        assert!(vicinity.contains(&PointN::new([0, -1, 4])));
+
+        assert_eq!(PointN::<4>::origin().get_vicinity().len(), 80);
+        assert_eq!(PointN::<5>::origin().get_vicinity().len(), 242);
+    }
+
+    #[test]
+    fn test_convert() {
+        let p = PointN::new([1, 2]);
+        assert_eq!(p.convert::<4>(), PointN::new([1, 2, 0, 0]));
+        assert_eq!(PointN::new([1, 2, 3]).convert::<2>(), p);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let (a, b) = (PointN::new([1, 2, 3]), PointN::from([3, 2, 1]));
+
+        assert_eq!(a + b, PointN::new([4, 4, 4]));
+        assert_eq!(a - b, PointN::new([-2, 0, 2]));
+        assert_eq!(-a, PointN::new([-1, -2, -3]));
+        assert_eq!(a * 2, PointN::new([2, 4, 6]));
+    }
+}
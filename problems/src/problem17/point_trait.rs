@@ -1,3 +1,3 @@
-pub(crate) trait Point: Sized {
+pub trait Point: Sized {
     fn get_vicinity(&self) -> Vec<Self>;
 }
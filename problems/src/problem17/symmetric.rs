@@ -0,0 +1,232 @@
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+};
+
+use utils::{check_deadline, Automaton, ProblemResult, Rules};
+
+use super::PointN;
+
+// coordinates beyond x and y start at zero and the rules don't tell one axis from another,
+// so the universe stays symmetric under flipping the sign of any of them and under any of
+// their permutations, only cells with non-negative and sorted extra coordinates are simulated,
+// each one standing for its whole class of `weight` symmetric cells
+struct Class {
+    coords: Vec<u8>,
+    weight: u64,
+    // classes of the cells around the representative, the representative itself included,
+    // with the number of such cells in each class, computed on demand
+    neighbours: Option<Vec<(usize, u64)>>,
+}
+
+// number of distinct cells the sorted non-negative coordinates stand for
+fn weight(coords: &[u8]) -> u64 {
+    let factorial = |n: usize| (1..=n as u64).product::<u64>();
+
+    let signs = 1 << coords.iter().filter(|c| **c != 0).count();
+    let mut permutations = factorial(coords.len());
+
+    let mut idx = 0;
+    while idx < coords.len() {
+        let same = coords[idx..].iter().take_while(|c| **c == coords[idx]).count();
+        permutations /= factorial(same);
+        idx += same;
+    }
+
+    signs * permutations
+}
+
+// simulation of the Conway cubes in any number of dimensions from 2 on
+pub struct SymmetricCubes {
+    // number of coordinates beyond x and y
+    extra: usize,
+    classes: Vec<Class>,
+    class_ids: HashMap<Vec<u8>, usize>,
+    // alive representatives, (x, y, class id)
+    alive: HashSet<(isize, isize, usize)>,
+    rules: Rules,
+    generation: usize,
+}
+
+impl SymmetricCubes {
+    pub fn new(slice: &[PointN<2>], dimensions: usize, rules: Rules) -> Self {
+        assert!(dimensions >= 2);
+
+        let mut cubes = SymmetricCubes {
+            extra: dimensions - 2,
+            classes: vec![],
+            class_ids: HashMap::new(),
+            alive: HashSet::new(),
+            rules,
+            generation: 0,
+        };
+
+        let zero = cubes.class_id(vec![0; dimensions - 2]);
+        cubes.alive = slice
+            .iter()
+            .map(|p| (p.coords[0], p.coords[1], zero))
+            .collect();
+
+        cubes
+    }
+
+    fn class_id(&mut self, coords: Vec<u8>) -> usize {
+        if let Some(id) = self.class_ids.get(&coords) {
+            return *id;
+        }
+
+        let id = self.classes.len();
+        self.class_ids.insert(coords.clone(), id);
+        self.classes.push(Class {
+            weight: weight(&coords),
+            coords,
+            neighbours: None,
+        });
+
+        id
+    }
+
+    fn cache_neighbours(&mut self, id: usize) {
+        if self.classes[id].neighbours.is_some() {
+            return;
+        }
+
+        let coords = self.classes[id].coords.clone();
+        let mut counts: HashMap<usize, u64> = HashMap::new();
+
+        // every combination of -1, 0 and 1 offsets, mapped back into the sorted orthant
+        for code in 0..3usize.pow(self.extra as u32) {
+            let mut rest = code;
+            let mut neighbour: Vec<u8> = coords
+                .iter()
+                .map(|c| {
+                    let offset = (rest % 3) as isize - 1;
+                    rest /= 3;
+                    (*c as isize + offset).unsigned_abs() as u8
+                })
+                .collect();
+            neighbour.sort_unstable();
+
+            *counts.entry(self.class_id(neighbour)).or_default() += 1;
+        }
+
+        self.classes[id].neighbours = Some(counts.into_iter().collect());
+    }
+
+    // a single generation, `check` is called for every alive cell and stops the step
+    // with its error before anything has changed
+    fn advance<E>(&mut self, mut check: impl FnMut() -> Result<(), E>) -> Result<bool, E> {
+        let ids: HashSet<usize> = self.alive.iter().map(|(_, _, id)| *id).collect();
+        for id in ids {
+            self.cache_neighbours(id);
+        }
+
+        // for every cell next to an alive one, the sum of class weights of alive cells around it,
+        // multiplied by its own class weight, including the cell itself
+        let mut sums: HashMap<(isize, isize, usize), u64> = HashMap::new();
+
+        for &(x, y, id) in &self.alive {
+            check()?;
+
+            let class = &self.classes[id];
+            for &(neighbour, count) in class.neighbours.as_deref().unwrap_or_default() {
+                for dx in -1..=1 {
+                    for dy in -1..=1 {
+                        *sums.entry((x + dx, y + dy, neighbour)).or_default() +=
+                            count * class.weight;
+                    }
+                }
+            }
+        }
+
+        let next: HashSet<_> = sums
+            .into_iter()
+            .filter(|(cell, sum)| {
+                let alive = self.alive.contains(cell);
+                let count = sum / self.classes[cell.2].weight - alive as u64;
+                self.rules.next(alive, count as usize)
+            })
+            .map(|(cell, _)| cell)
+            .collect();
+
+        let changed = next != self.alive;
+        self.alive = next;
+        self.generation += 1;
+
+        Ok(changed)
+    }
+}
+
+impl Automaton for SymmetricCubes {
+    fn step(&mut self) -> bool {
+        let Ok(changed) = self.advance(|| Ok::<_, Infallible>(()));
+        changed
+    }
+
+    // checks the deadline for every alive cell, a single step takes a while in high dimensions
+    fn try_step(&mut self) -> ProblemResult<bool> {
+        Ok(self.advance(check_deadline)?)
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn population(&self) -> usize {
+        self.alive
+            .iter()
+            .map(|(_, _, id)| self.classes[*id].weight as usize)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{weight, SymmetricCubes};
+    use crate::problem17::{Point, PointN};
+    use utils::{set_deadline, Automaton, SparseAutomaton};
+    use std::time::Instant;
+
+    #[test]
+    fn test_weight() {
+        assert_eq!(weight(&[]), 1);
+        assert_eq!(weight(&[0, 0]), 1);
+        assert_eq!(weight(&[0, 1]), 4);
+        assert_eq!(weight(&[1, 1]), 4);
+        assert_eq!(weight(&[1, 2]), 8);
+        assert_eq!(weight(&[0, 1, 1]), 12);
+    }
+
+    #[test]
+    fn test_against_sparse() {
+        let slice = vec![
+            PointN::new([1, 0]),
+            PointN::new([2, 1]),
+            PointN::new([0, 2]),
+            PointN::new([1, 2]),
+            PointN::new([2, 2]),
+        ];
+
+        let mut cubes = SymmetricCubes::new(&slice, 4, "B3/S23".parse().unwrap());
+        let mut sparse = SparseAutomaton::new(
+            slice.iter().map(PointN::convert::<4>),
+            "B3/S23".parse().unwrap(),
+            &PointN::<4>::get_vicinity,
+        );
+
+        for _ in 0..4 {
+            cubes.step();
+            sparse.step();
+            assert_eq!(cubes.population(), sparse.population());
+        }
+
+        // a passed deadline stops a step before it has changed anything
+        set_deadline(Some(Instant::now()));
+        assert!(cubes.try_step().is_err());
+        set_deadline(None);
+        assert_eq!(cubes.population(), sparse.population());
+        assert!(cubes.try_step().unwrap());
+        sparse.step();
+        assert_eq!(cubes.population(), sparse.population());
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt::{self, Display, Formatter};
+
+use failure::{format_err, Error};
+use num::{BigInt, ToPrimitive, Zero};
+
+use super::Operator;
+
+// expression tree, the grouping the precedence table has implied is explicit here
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Expr {
+    Number(BigInt),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub fn binary(op: Operator, lhs: Expr, rhs: Expr) -> Self {
+        Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+    }
+
+    // evaluates in 64 bits, fails instead of overflowing
+    pub fn eval_checked(&self) -> Result<i64, Error> {
+        match self {
+            Expr::Number(n) => n
+                .to_i64()
+                .ok_or_else(|| format_err!("{} doesn't fit into 64 bits", n)),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval_checked()?, rhs.eval_checked()?);
+
+                let value = match op {
+                    Operator::Add => lhs.checked_add(rhs),
+                    Operator::Sub => lhs.checked_sub(rhs),
+                    Operator::Mul => lhs.checked_mul(rhs),
+                    Operator::Div if rhs == 0 => return Err(format_err!("division by zero")),
+                    Operator::Div => lhs.checked_div(rhs),
+                };
+
+                value.ok_or_else(|| {
+                    format_err!("overflow in {} {} {}", lhs, op.symbol(), rhs)
+                })
+            }
+        }
+    }
+
+    // evaluates with no limit on the size of numbers, division truncates towards zero
+    pub fn eval_big(&self) -> Result<BigInt, Error> {
+        match self {
+            Expr::Number(n) => Ok(n.clone()),
+            Expr::Binary(op, lhs, rhs) => apply(*op, lhs.eval_big()?, rhs.eval_big()?),
+        }
+    }
+
+    // the same expression with its leftmost innermost operation done, none for a number
+    pub fn reduce(&self) -> Result<Option<Expr>, Error> {
+        match self {
+            Expr::Number(_) => Ok(None),
+            Expr::Binary(op, lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+                (Expr::Number(lhs), Expr::Number(rhs)) => {
+                    Ok(Some(Expr::Number(apply(*op, lhs.clone(), rhs.clone())?)))
+                }
+                _ => Ok(Some(match lhs.reduce()? {
+                    Some(lhs) => Expr::binary(*op, lhs, rhs.as_ref().clone()),
+                    None => Expr::binary(*op, lhs.as_ref().clone(), rhs.reduce()?.unwrap()),
+                })),
+            },
+        }
+    }
+
+    // the expression itself followed by every reduction down to a single number
+    pub fn reductions(&self) -> Result<Vec<Expr>, Error> {
+        let mut steps = vec![self.clone()];
+
+        while let Some(next) = steps[steps.len() - 1].reduce()? {
+            steps.push(next);
+        }
+
+        Ok(steps)
+    }
+}
+
+fn apply(op: Operator, lhs: BigInt, rhs: BigInt) -> Result<BigInt, Error> {
+    match op {
+        Operator::Add => Ok(lhs + rhs),
+        Operator::Sub => Ok(lhs - rhs),
+        Operator::Mul => Ok(lhs * rhs),
+        Operator::Div if rhs.is_zero() => Err(format_err!("division by zero")),
+        Operator::Div => Ok(lhs / rhs),
+    }
+}
+
+// every operation but the outermost one goes into parens, e.g. `2 * (3 + (4 * 5))`
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let operand = |expr: &Expr| match expr {
+            Expr::Number(_) => expr.to_string(),
+            Expr::Binary(..) => format!("({})", expr),
+        };
+
+        match self {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Binary(op, lhs, rhs) => {
+                write!(f, "{} {} {}", operand(lhs), op.symbol(), operand(rhs))
+            }
+        }
+    }
+}
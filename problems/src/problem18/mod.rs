@@ -1,150 +1,578 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
 use failure::{format_err, Error};
-use utils::{result, RetTypes};
+use num::BigInt;
+use utils::{parse_token, ParseError, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
+
+mod expr;
+pub use expr::Expr;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+const OPERATORS: [Operator; 4] = [Operator::Add, Operator::Sub, Operator::Mul, Operator::Div];
 
-#[derive(PartialEq, Clone, Debug)]
-enum Token {
-    Plus,
-    Mult,
-    Number(usize),
+impl Operator {
+    pub fn symbol(self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Sub => '-',
+            Operator::Mul => '*',
+            Operator::Div => '/',
+        }
+    }
+
+    pub fn from_symbol(c: char) -> Option<Self> {
+        OPERATORS.iter().copied().find(|op| op.symbol() == c)
+    }
+}
+
+// numbers are kept whole, only `Expr::eval_checked` limits them to 64 bits
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Token {
+    Number(BigInt),
+    Operator(Operator),
     OpenParen,
     CloseParen,
 }
 
-fn compute_expr(sub_expr: &[Token]) -> Result<usize, Error> {
-    if let Token::Number(mut net) = sub_expr[0] {
-        let tail = sub_expr[1..].to_vec();
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Operator(op) => write!(f, "{}", op.symbol()),
+            Token::OpenParen => write!(f, "("),
+            Token::CloseParen => write!(f, ")"),
+        }
+    }
+}
 
-        let mut add_n = |net, n| net + n;
-        let mut mult_n = |net, n| net * n;
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Assoc {
+    Left,
+    Right,
+}
 
-        let mut last_op: &mut dyn FnMut(usize, usize) -> usize = &mut add_n;
+// binding level and associativity of every operator, the higher the level the tighter
+// the operator binds, operators without a level can't be used at all
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Precedence {
+    levels: [Option<(u8, Assoc)>; 4],
+}
 
-        // evalute forward
-        for tok in tail.iter() {
-            match tok {
-                Token::Number(n) => {
-                    net = last_op(net, *n);
-                }
-                Token::Plus => last_op = &mut add_n,
-                Token::Mult => last_op = &mut mult_n,
-                _ => {}
+impl Precedence {
+    // the first star, everything is evaluated left to right
+    pub const EQUAL: Precedence = Precedence {
+        levels: [Some((1, Assoc::Left)); 4],
+    };
+
+    // the second star, addition and subtraction go before multiplication and division
+    pub const ADDITION_FIRST: Precedence = Precedence {
+        levels: [
+            Some((2, Assoc::Left)),
+            Some((2, Assoc::Left)),
+            Some((1, Assoc::Left)),
+            Some((1, Assoc::Left)),
+        ],
+    };
+
+    pub fn new() -> Self {
+        Precedence::default()
+    }
+
+    // `level` starts from 1
+    pub fn with(mut self, op: Operator, level: u8, assoc: Assoc) -> Self {
+        assert!(level > 0);
+        self.levels[op as usize] = Some((level, assoc));
+        self
+    }
+
+    pub fn get(&self, op: Operator) -> Option<(u8, Assoc)> {
+        self.levels[op as usize]
+    }
+}
+
+impl FromStr for Precedence {
+    type Err = Error;
+
+    // comma separated `<operator>=<level>` entries with an `r` after the level
+    // for right associative operators, e.g. `+=2,*=1` for the second star
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut precedence = Precedence::new();
+
+        for entry in s.split(',') {
+            let wrong = || format_err!("wrong precedence '{}', expected <operator>=<level>", entry);
+
+            let (op, level) = entry.trim().split_once('=').ok_or_else(wrong)?;
+
+            let mut chars = op.chars();
+            let op = match (chars.next().and_then(Operator::from_symbol), chars.next()) {
+                (Some(op), None) => op,
+                _ => return Err(wrong()),
+            };
+
+            let (level, assoc) = match level.strip_suffix('r') {
+                Some(level) => (level, Assoc::Right),
+                None => (level, Assoc::Left),
+            };
+
+            match level.parse::<u8>() {
+                Ok(level) if level > 0 => precedence = precedence.with(op, level, assoc),
+                _ => return Err(wrong()),
             }
         }
 
-        return Ok(net);
+        Ok(precedence)
     }
+}
+
+impl Display for Precedence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let entries: Vec<String> = OPERATORS
+            .iter()
+            .filter_map(|op| {
+                self.get(*op).map(|(level, assoc)| {
+                    let suffix = if assoc == Assoc::Right { "r" } else { "" };
+                    format!("{}={}{}", op.symbol(), level, suffix)
+                })
+            })
+            .collect();
 
-    Err(format_err!("wrong expr {:?}", sub_expr))
+        write!(f, "{}", entries.join(","))
+    }
 }
 
-fn eval_parens(mut stack: Vec<Token>) -> Result<Vec<Token>, Error> {
-    let mut sub_expr = vec![];
+// both the parser and every pass over the tree are recursive, these keep the tree of a single
+// expression shallow enough for the stack of a worker thread
+pub const MAX_OPERATORS: usize = 500;
+pub const MAX_NESTING: usize = 100;
+
+// precedence climbing over the tokens of a single expression
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    precedence: &'a Precedence,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
 
-    // back-trace for open paren
-    while !stack.is_empty() {
-        let tok = stack.pop().unwrap();
-        if tok == Token::OpenParen {
-            break;
+    // a number or an expression in parens
+    fn operand(&mut self) -> Result<Expr, Error> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n.clone())),
+            Some(Token::OpenParen) => {
+                let value = self.expr(0)?;
+                match self.next() {
+                    Some(Token::CloseParen) => Ok(value),
+                    _ => Err(format_err!("missing close paren")),
+                }
+            }
+            Some(token) => Err(format_err!("expected a number, found '{}'", token)),
+            None => Err(format_err!("unexpected end of expression")),
         }
-        sub_expr.push(tok);
     }
 
-    sub_expr.reverse();
-    stack.push(Token::Number(compute_expr(&sub_expr)?));
+    // operators binding at `min_level` or tighter, it is one past the highest level
+    // after a left associative operator of level 255, hence u16
+    fn expr(&mut self, min_level: u16) -> Result<Expr, Error> {
+        let mut lhs = self.operand()?;
 
-    Ok(stack)
-}
+        while let Some(&Token::Operator(op)) = self.tokens.get(self.pos) {
+            let (level, assoc) = self
+                .precedence
+                .get(op)
+                .ok_or_else(|| format_err!("operator '{}' has no precedence", op.symbol()))?;
 
-fn first_star(input: &[Vec<Token>]) -> Result<usize, Error> {
-    let mut net = 0;
-    for expr in input {
-        let mut stack: Vec<Token> = vec![];
-        for tok in expr.iter() {
-            if tok == &Token::CloseParen {
-                stack = eval_parens(stack)?;
-            } else {
-                stack.push(tok.clone());
+            let level = u16::from(level);
+            if level < min_level {
+                break;
             }
+            self.pos += 1;
+
+            let rhs = match assoc {
+                Assoc::Left => self.expr(level + 1)?,
+                Assoc::Right => self.expr(level)?,
+            };
+            lhs = Expr::binary(op, lhs, rhs);
         }
-        net += compute_expr(&stack)?;
+
+        Ok(lhs)
     }
-    Ok(net)
 }
 
-fn fold_addition(mut stack: Vec<Token>) -> Result<Vec<Token>, Error> {
-    if stack.len() > 1 && stack[stack.len() - 2] == Token::Plus {
-        let n = compute_expr(&stack[stack.len() - 3..])?;
-        stack = stack[..stack.len() - 3].to_vec();
-        stack.push(Token::Number(n));
+// tree of a single expression grouped by the precedence table
+pub fn build(expr: &[Token], precedence: &Precedence) -> Result<Expr, Error> {
+    let operators = expr
+        .iter()
+        .filter(|token| matches!(token, Token::Operator(_)))
+        .count();
+    let nesting = expr
+        .iter()
+        .scan(0isize, |depth, token| {
+            match token {
+                Token::OpenParen => *depth += 1,
+                Token::CloseParen => *depth -= 1,
+                _ => {}
+            }
+            Some(*depth)
+        })
+        .max()
+        .unwrap_or(0);
+
+    if operators > MAX_OPERATORS || nesting > MAX_NESTING as isize {
+        return Err(format_err!(
+            "expression is too big, at most {} operators and {} nested parens are allowed",
+            MAX_OPERATORS,
+            MAX_NESTING
+        ));
     }
 
-    Ok(stack)
+    let mut parser = Parser {
+        tokens: expr,
+        pos: 0,
+        precedence,
+    };
+
+    let tree = parser.expr(0)?;
+    match parser.next() {
+        None => Ok(tree),
+        Some(token) => Err(format_err!("unexpected '{}'", token)),
+    }
 }
 
-fn second_star(input: &[Vec<Token>]) -> Result<usize, Error> {
-    let mut net = 0;
+// evaluates a single expression, fails on overflow
+pub fn eval(expr: &[Token], precedence: &Precedence) -> Result<i64, Error> {
+    build(expr, precedence)?.eval_checked()
+}
+
+fn sum(input: &[Vec<Token>], precedence: &Precedence) -> Result<i64, Error> {
+    input.iter().try_fold(0i64, |total, expr| {
+        total
+            .checked_add(eval(expr, precedence)?)
+            .ok_or_else(|| format_err!("overflow in the sum of expressions"))
+    })
+}
 
-    for expr in input {
-        let mut stack: Vec<Token> = vec![];
+// tokenizes a single expression and checks that operands and operators alternate and
+// the expression is within the limits, so that only the precedence table is left for `build`
+// to fail on
+pub fn parse_expr(line: &str) -> ParseResult<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut operators = 0;
+    // unclosed parens so far
+    let mut parens = vec![];
+    // a number or an open paren goes next, otherwise an operator or a close paren
+    let mut operand = true;
 
-        for tok in expr.iter() {
-            match tok {
-                Token::CloseParen => {
-                    stack = eval_parens(stack)?;
-                    // after evaluating expresion in parentesis, look back and compute possible nearest '+' operation if exists
-                    // and push the result back into stack, for example:
-                    // 3 + (2 * 3) * 2 => 3 + (6) * 2 => 9 * 2 = 18
-                    stack = fold_addition(stack)?;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        let c_str = &line[idx..idx + c.len_utf8()];
+
+        let token = match c {
+            ' ' => continue,
+            '0'..='9' => {
+                let mut end = idx + 1;
+                while let Some((next, '0'..='9')) = chars.peek().copied() {
+                    end = next + 1;
+                    chars.next();
                 }
-                Token::Number(_) => {
-                    stack.push(tok.clone());
-                    // if we encountered a number, try to look back and compute possible nearest '+' operation if exists
-                    // and push the result back into stack, for example:
-                    // 3 + 2 * 4 => 5 * 4 => 20
-                    stack = fold_addition(stack)?;
+                let text = &line[idx..end];
+                if !operand {
+                    return Err(ParseError::at(text, "expected an operator, got"));
                 }
-                _ => stack.push(tok.clone()),
-            };
-        }
+                Token::Number(parse_token(text, "number")?)
+            }
+            '(' => {
+                if !operand {
+                    return Err(ParseError::at(c_str, "expected an operator, got"));
+                }
+                if parens.len() == MAX_NESTING {
+                    return Err(ParseError::at(
+                        c_str,
+                        format!("parens nested deeper than {}, got", MAX_NESTING),
+                    ));
+                }
+                parens.push(c_str);
+                Token::OpenParen
+            }
+            ')' => {
+                if operand {
+                    return Err(ParseError::at(c_str, "expected a number, got"));
+                }
+                parens
+                    .pop()
+                    .ok_or_else(|| ParseError::at(c_str, "unmatched paren"))?;
+                Token::CloseParen
+            }
+            _ => match Operator::from_symbol(c) {
+                Some(_) if operand => {
+                    return Err(ParseError::at(c_str, "expected a number, got"))
+                }
+                Some(_) if operators == MAX_OPERATORS => {
+                    return Err(ParseError::at(
+                        c_str,
+                        format!("more than {} operators, got", MAX_OPERATORS),
+                    ))
+                }
+                Some(op) => {
+                    operators += 1;
+                    Token::Operator(op)
+                }
+                None => return Err(ParseError::at(c_str, "unexpected symbol")),
+            },
+        };
+
+        operand = matches!(token, Token::Operator(_) | Token::OpenParen);
+        tokens.push(token);
+    }
+
+    if operand {
+        return Err(ParseError::after(line, "unexpected end of expression"));
+    }
 
-        net += compute_expr(&stack)?;
+    if let Some(paren) = parens.pop() {
+        return Err(ParseError::at(paren, "unclosed paren"));
     }
 
-    Ok(net)
+    Ok(tokens)
 }
 
-fn parse(input_raw: &str) -> Result<Vec<Vec<Token>>, Error> {
-    let mut res = vec![];
+// one expression per line
+pub fn parse(input_raw: &str) -> ParseResult<Vec<Vec<Token>>> {
+    input_raw.lines().map(parse_expr).collect()
+}
 
-    for line in input_raw.lines() {
-        let mut tmp = vec![];
-        let normalized = line.replace(' ', "");
-        for c in normalized.chars() {
-            let token = match c {
-                '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' => {
-                    Token::Number(c.to_digit(10).unwrap() as usize)
-                }
-                '(' => Token::OpenParen,
-                ')' => Token::CloseParen,
-                '+' => Token::Plus,
-                '*' => Token::Mult,
-                _ => return Err(format_err!("wrong input")),
-            };
-            tmp.push(token);
-        }
-        res.push(tmp);
+pub struct Day18;
+
+impl Problem for Day18 {
+    const DAY: usize = 18;
+    const TITLE: &'static str = "Operation Order";
+    const INPUT: &'static str = include_str!("./input");
+
+    type Input = Vec<Vec<Token>>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
     }
 
-    Ok(res)
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        sum(input, &Precedence::EQUAL)
+    }
+
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        sum(input, &Precedence::ADDITION_FIRST)
+    }
 }
 
-#[allow(clippy::unnecessary_wraps)]
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let parsed = parse(input_raw)?;
+#[cfg(test)]
+mod tests {
+    use super::{
+        build, eval, parse, parse_expr, Assoc, Operator, Precedence, Token, MAX_NESTING,
+        MAX_OPERATORS,
+    };
+    use num::BigInt;
+
+    fn eval_str(expr: &str, precedence: &Precedence) -> i64 {
+        eval(&parse_expr(expr).unwrap(), precedence).unwrap()
+    }
+
+    #[test]
+    fn test_eval() {
+        let expr = "2 * 3 + (4 * 5)";
+        assert_eq!(eval_str(expr, &Precedence::EQUAL), 26);
+        assert_eq!(eval_str(expr, &Precedence::ADDITION_FIRST), 46);
+
+        let expr = "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2";
+        assert_eq!(eval_str(expr, &Precedence::EQUAL), 13632);
+        assert_eq!(eval_str(expr, &Precedence::ADDITION_FIRST), 23340);
+
+        assert_eq!(eval_str("12 * (30 - 5) / 7", &Precedence::EQUAL), 42);
+        assert_eq!(eval_str("10 - 2 * 3", &Precedence::ADDITION_FIRST), 24);
+    }
+
+    #[test]
+    fn test_precedence() {
+        // the usual arithmetic with a right associative subtraction for a change
+        let precedence: Precedence = "+=1,-=1r,*=2,/=2".parse().unwrap();
+        assert_eq!(
+            precedence,
+            Precedence::new()
+                .with(Operator::Add, 1, Assoc::Left)
+                .with(Operator::Sub, 1, Assoc::Right)
+                .with(Operator::Mul, 2, Assoc::Left)
+                .with(Operator::Div, 2, Assoc::Left)
+        );
+        assert_eq!(precedence.to_string(), "+=1,-=1r,*=2,/=2");
+        assert_eq!(Precedence::ADDITION_FIRST.to_string(), "+=2,-=2,*=1,/=1");
+
+        assert_eq!(eval_str("2 + 3 * 4", &precedence), 14);
+        assert_eq!(eval_str("10 - 4 - 3", &precedence), 9);
+        assert_eq!(eval_str("10 - 4 - 3", &Precedence::EQUAL), 3);
+
+        // operators missing from the table can't be used
+        let only_addition: Precedence = "+=1".parse().unwrap();
+        assert!(eval(&parse_expr("1 * 2").unwrap(), &only_addition).is_err());
+
+        assert!("+".parse::<Precedence>().is_err());
+        assert!("+=0".parse::<Precedence>().is_err());
+        assert!("%=1".parse::<Precedence>().is_err());
+        assert!("+=1x".parse::<Precedence>().is_err());
+        assert!("+=256".parse::<Precedence>().is_err());
+
+        // the highest level is no different from the others
+        let addition_first: Precedence = "+=255,*=1".parse().unwrap();
+        assert_eq!(eval_str("1 + 2 * 3 + 4", &addition_first), 21);
+        let right_assoc: Precedence = "-=255r".parse().unwrap();
+        assert_eq!(eval_str("10 - 4 - 3", &right_assoc), 9);
+    }
+
+    #[test]
+    fn test_expr() {
+        let tree = |expr, precedence| build(&parse_expr(expr).unwrap(), precedence).unwrap();
+
+        let expr = "2 * 3 + (4 * 5)";
+        assert_eq!(
+            tree(expr, &Precedence::EQUAL).to_string(),
+            "(2 * 3) + (4 * 5)"
+        );
+
+        let steps: Vec<_> = tree(expr, &Precedence::ADDITION_FIRST)
+            .reductions()
+            .unwrap()
+            .iter()
+            .map(|step| step.to_string())
+            .collect();
+        assert_eq!(
+            steps,
+            vec!["2 * (3 + (4 * 5))", "2 * (3 + 20)", "2 * 23", "46"]
+        );
 
-    Ok(RetTypes::Usize(result(
-        first_star(&parsed),
-        second_star(&parsed),
-    )))
+        // 2^64 overflows, but not in big integers
+        let expr = tree("4294967296 * 4294967296 - 1", &Precedence::EQUAL);
+        assert!(expr.eval_checked().is_err());
+        assert_eq!(
+            expr.eval_big().unwrap(),
+            "18446744073709551615".parse::<BigInt>().unwrap()
+        );
+
+        let expr = tree("9223372036854775807 + 1 - 1", &Precedence::EQUAL);
+        assert!(expr.eval_checked().is_err());
+        assert_eq!(expr.reductions().unwrap().len(), 3);
+
+        assert!(tree("1 / (2 - 2)", &Precedence::EQUAL).eval_big().is_err());
+
+        // literals wider than 64 bits are fine until they are evaluated in 64 bits
+        let expr = tree("99999999999999999999 / 10", &Precedence::EQUAL);
+        assert!(expr.eval_checked().is_err());
+        assert_eq!(expr.to_string(), "99999999999999999999 / 10");
+        assert_eq!(
+            expr.eval_big().unwrap(),
+            "9999999999999999999".parse::<BigInt>().unwrap()
+        );
+        assert!(eval(
+            &parse_expr("99999999999999999999").unwrap(),
+            &Precedence::EQUAL
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_limits() {
+        let chain = |op: &str, count: usize| format!("1{}", format!(" {} 1", op).repeat(count));
+        let nested = |expr: &str| {
+            let (open, close) = ("(".repeat(MAX_NESTING), ")".repeat(MAX_NESTING));
+            format!("{}{}{}", open, expr, close)
+        };
+
+        // the biggest expressions are fine on the stack of a test thread in debug builds
+        let right: Precedence = "-=1r".parse().unwrap();
+        assert_eq!(eval_str(&chain("+", MAX_OPERATORS), &Precedence::EQUAL), 501);
+        assert_eq!(eval_str(&nested(&chain("-", MAX_OPERATORS)), &right), 1);
+
+        let tokens = parse_expr(&nested(&chain("-", MAX_OPERATORS))).unwrap();
+        let expr = build(&tokens, &right).unwrap();
+        assert_eq!(expr.reductions().unwrap().len(), MAX_OPERATORS + 1);
+
+        let err = |input: &str| {
+            let err = parse_expr(input).unwrap_err().locate(input);
+            (err.column, err.to_string())
+        };
+        assert_eq!(
+            err(&chain("+", MAX_OPERATORS + 1)),
+            (
+                Some(2003),
+                "line 1, column 2003: more than 500 operators, got '+'".to_owned()
+            )
+        );
+        assert_eq!(err(&chain("+", 20000)).0, Some(2003));
+        assert_eq!(
+            err(&format!("{}1{}", "(".repeat(50000), ")".repeat(50000))),
+            (
+                Some(101),
+                "line 1, column 101: parens nested deeper than 100, got '('".to_owned()
+            )
+        );
+
+        // tokens made by hand are checked too
+        let mut tokens = vec![Token::OpenParen; MAX_NESTING + 1];
+        tokens.push(Token::Number(1.into()));
+        tokens.extend(vec![Token::CloseParen; MAX_NESTING + 1]);
+        assert!(build(&tokens, &Precedence::EQUAL).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("1 + 2\n3 * 4").unwrap().len(), 2);
+        assert_eq!(parse_expr("123+4").unwrap().len(), 3);
+        assert!(parse_expr("(1 + 2").is_err());
+        assert!(parse_expr("1 + 2)").is_err());
+        assert!(parse_expr("1 % 2").is_err());
+
+        assert!(eval(&parse_expr("4 / (2 - 2)").unwrap(), &Precedence::EQUAL).is_err());
+
+        let err = |input| {
+            let err = parse(input).unwrap_err().locate(input);
+            (err.line, err.column, err.to_string())
+        };
+        assert_eq!(
+            err("1 + 2\n(1 + 2) 3"),
+            (
+                Some(2),
+                Some(9),
+                "line 2, column 9: expected an operator, got '3'".to_owned()
+            )
+        );
+        assert_eq!(
+            err("1 +"),
+            (
+                Some(1),
+                Some(4),
+                "line 1, column 4: unexpected end of expression".to_owned()
+            )
+        );
+        assert_eq!(err("1 2").1, Some(3));
+        assert_eq!(err("2 (3)").1, Some(3));
+        assert_eq!(err("* 2").1, Some(1));
+        assert_eq!(err("()").1, Some(2));
+        assert_eq!(err("1 + (2 *)").1, Some(9));
+        assert!(parse_expr("").is_err());
+    }
 }
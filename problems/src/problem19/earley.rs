@@ -0,0 +1,484 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use failure::format_err;
+use utils::ProblemResult;
+
+use super::{Mismatch, Node, Rules, Tree};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Symbol {
+    // index of the rule in `Grammar::alternatives`
+    Rule(usize),
+    Term(Vec<u8>),
+}
+
+// partially matched alternative of a rule, `dot` symbols of it are matched already
+// starting from the position `origin` of the message
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Item {
+    rule: usize,
+    alt: usize,
+    dot: usize,
+    origin: usize,
+}
+
+// turns rules into plain alternatives of symbols, groups and operators become auxiliary rules
+// appended after the rules themselves
+struct Compiler {
+    // dense index of every rule number
+    index: HashMap<usize, usize>,
+    alternatives: Vec<Vec<Vec<Symbol>>>,
+}
+
+impl Compiler {
+    fn alternatives_of(&mut self, node: &Node) -> ProblemResult<Vec<Vec<Symbol>>> {
+        match node {
+            Node::Alt(nodes) => nodes.iter().map(|node| self.seq(node)).collect(),
+            _ => Ok(vec![self.seq(node)?]),
+        }
+    }
+
+    fn seq(&mut self, node: &Node) -> ProblemResult<Vec<Symbol>> {
+        match node {
+            Node::Seq(nodes) => nodes.iter().map(|node| self.symbol(node)).collect(),
+            _ => Ok(vec![self.symbol(node)?]),
+        }
+    }
+
+    fn symbol(&mut self, node: &Node) -> ProblemResult<Symbol> {
+        let symbol = match node {
+            Node::Rule(id) => match self.index.get(id) {
+                Some(idx) => Symbol::Rule(*idx),
+                None => return Err(format_err!("unknown rule {}", id)),
+            },
+            Node::Term(term) => Symbol::Term(term.as_bytes().to_vec()),
+            Node::Seq(_) | Node::Alt(_) => {
+                let alternatives = self.alternatives_of(node)?;
+                self.aux(alternatives)
+            }
+            Node::Optional(node) => {
+                let inner = self.symbol(node)?;
+                self.aux(vec![vec![], vec![inner]])
+            }
+            // left recursive, which the Earley algorithm handles best
+            Node::ZeroOrMore(inner) | Node::OneOrMore(inner) => {
+                let inner = self.symbol(inner)?;
+                let first = match node {
+                    Node::ZeroOrMore(_) => vec![],
+                    _ => vec![inner.clone()],
+                };
+                let rule = self.alternatives.len();
+                self.aux(vec![first, vec![Symbol::Rule(rule), inner]])
+            }
+        };
+
+        Ok(symbol)
+    }
+
+    fn aux(&mut self, alternatives: Vec<Vec<Symbol>>) -> Symbol {
+        self.alternatives.push(alternatives);
+        Symbol::Rule(self.alternatives.len() - 1)
+    }
+}
+
+// matches messages against any set of rules, recursive ones included, with the Earley algorithm
+pub struct Grammar {
+    // alternatives of every rule, rules are renumbered densely
+    alternatives: Vec<Vec<Vec<Symbol>>>,
+    // rules which match an empty message
+    nullable: Vec<bool>,
+    // rule numbers by their indices, auxiliary rules come after all of them
+    ids: Vec<usize>,
+    start: usize,
+}
+
+impl Grammar {
+    pub fn new(rules: &Rules, rule_id: usize) -> ProblemResult<Self> {
+        if !rules.contains_key(&rule_id) {
+            return Err(format_err!("unknown rule {}", rule_id));
+        }
+
+        let mut ids: Vec<usize> = rules.keys().copied().collect();
+        ids.sort_unstable();
+        let index: HashMap<usize, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let start = index[&rule_id];
+
+        let mut compiler = Compiler {
+            index,
+            alternatives: vec![vec![]; ids.len()],
+        };
+
+        for (idx, id) in ids.iter().enumerate() {
+            compiler.alternatives[idx] = compiler.alternatives_of(&rules[id])?;
+        }
+
+        let alternatives = compiler.alternatives;
+
+        let mut grammar = Grammar {
+            nullable: vec![false; alternatives.len()],
+            alternatives,
+            ids,
+            start,
+        };
+        grammar.find_nullable();
+
+        Ok(grammar)
+    }
+
+    // a rule is nullable if any of its alternatives consists of nullable rules only
+    fn find_nullable(&mut self) {
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for rule in 0..self.alternatives.len() {
+                if self.nullable[rule] {
+                    continue;
+                }
+
+                let nullable = self.alternatives[rule].iter().any(|alt| {
+                    alt.iter().all(|symbol| match symbol {
+                        Symbol::Rule(rule) => self.nullable[*rule],
+                        Symbol::Term(term) => term.is_empty(),
+                    })
+                });
+
+                if nullable {
+                    self.nullable[rule] = true;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    fn symbol(&self, item: &Item) -> Option<&Symbol> {
+        self.alternatives[item.rule][item.alt].get(item.dot)
+    }
+
+    // items of every position of the message, none past the longest prefix some matching
+    // message starts with
+    fn chart(&self, message: &[u8]) -> Vec<Vec<Item>> {
+        let len = message.len();
+
+        // items of every position and the same items as sets to skip duplicates
+        let mut chart: Vec<Vec<Item>> = vec![vec![]; len + 1];
+        let mut seen: Vec<HashSet<Item>> = vec![HashSet::new(); len + 1];
+
+        let mut add = |chart: &mut Vec<Vec<Item>>, pos: usize, item: Item| {
+            if seen[pos].insert(item) {
+                chart[pos].push(item);
+            }
+        };
+
+        for alt in 0..self.alternatives[self.start].len() {
+            let item = Item {
+                rule: self.start,
+                alt,
+                dot: 0,
+                origin: 0,
+            };
+            add(&mut chart, 0, item);
+        }
+
+        for pos in 0..=len {
+            let mut idx = 0;
+
+            while idx < chart[pos].len() {
+                let item = chart[pos][idx];
+                idx += 1;
+
+                let advanced = Item {
+                    dot: item.dot + 1,
+                    ..item
+                };
+
+                match self.symbol(&item) {
+                    // complete, every item waiting for the rule moves on
+                    None => {
+                        let mut waiting = vec![];
+                        for parent in &chart[item.origin] {
+                            if self.symbol(parent) == Some(&Symbol::Rule(item.rule)) {
+                                waiting.push(Item {
+                                    dot: parent.dot + 1,
+                                    ..*parent
+                                });
+                            }
+                        }
+
+                        for parent in waiting {
+                            add(&mut chart, pos, parent);
+                        }
+                    }
+                    // predict, a nullable rule may be skipped right away
+                    Some(Symbol::Rule(rule)) => {
+                        for alt in 0..self.alternatives[*rule].len() {
+                            let predicted = Item {
+                                rule: *rule,
+                                alt,
+                                dot: 0,
+                                origin: pos,
+                            };
+                            add(&mut chart, pos, predicted);
+                        }
+
+                        if self.nullable[*rule] {
+                            add(&mut chart, pos, advanced);
+                        }
+                    }
+                    // scan
+                    Some(Symbol::Term(term)) => {
+                        if message[pos..].starts_with(term) {
+                            add(&mut chart, pos + term.len(), advanced);
+                        }
+                    }
+                }
+            }
+        }
+
+        chart
+    }
+
+    // the start rule matches everything up to the position
+    fn is_complete(&self, items: &[Item]) -> bool {
+        items.iter().any(|item| {
+            item.rule == self.start && item.origin == 0 && self.symbol(item).is_none()
+        })
+    }
+
+    pub fn is_match(&self, message: &[u8]) -> bool {
+        self.is_complete(&self.chart(message)[message.len()])
+    }
+
+    // the derivation tree of a matching message, or how far a message gets otherwise
+    pub fn explain(&self, message: &[u8]) -> Result<Tree, Mismatch> {
+        let len = message.len();
+        let chart = self.chart(message);
+
+        if !self.is_complete(&chart[len]) {
+            // there is an item at the start at least
+            let matched = (0..=len).rev().find(|pos| !chart[*pos].is_empty()).unwrap_or(0);
+
+            let mut rules = BTreeSet::new();
+            let mut terms = BTreeSet::new();
+
+            for item in &chart[matched] {
+                match self.symbol(item) {
+                    Some(Symbol::Rule(rule)) if *rule < self.ids.len() => {
+                        rules.insert(self.ids[*rule]);
+                    }
+                    Some(Symbol::Term(term)) => {
+                        terms.insert(String::from_utf8_lossy(term).into_owned());
+                    }
+                    _ => {}
+                }
+            }
+
+            return Err(Mismatch {
+                matched,
+                rules: rules.into_iter().collect(),
+                terms: terms.into_iter().collect(),
+                end: self.is_complete(&chart[matched]),
+            });
+        }
+
+        let mut derivation = Derivation {
+            grammar: self,
+            message,
+            completed: HashSet::new(),
+            spans: HashSet::new(),
+            stack: vec![],
+        };
+
+        for (pos, items) in chart.iter().enumerate() {
+            for item in items.iter().filter(|item| self.symbol(item).is_none()) {
+                derivation
+                    .completed
+                    .insert((item.rule, item.alt, item.origin, pos));
+                derivation.spans.insert((item.rule, item.origin, pos));
+            }
+        }
+
+        // the rule is complete, so some derivation exists
+        let children = derivation
+            .children(self.start, 0, len)
+            .expect("no derivation of a matching message");
+
+        Ok(Tree::Rule(self.ids[self.start], children))
+    }
+}
+
+// picks a derivation out of the complete items of a chart
+struct Derivation<'a> {
+    grammar: &'a Grammar,
+    message: &'a [u8],
+    // rule, alternative, start and end of every complete item
+    completed: HashSet<(usize, usize, usize, usize)>,
+    // the same without alternatives
+    spans: HashSet<(usize, usize, usize)>,
+    // rules being derived along with their spans, a rule deriving itself over the same span
+    // goes round in circles, there is a derivation without it
+    stack: Vec<(usize, usize, usize)>,
+}
+
+impl<'a> Derivation<'a> {
+    // nodes of the rule matching the span of the message
+    fn children(&mut self, rule: usize, start: usize, end: usize) -> Option<Vec<Tree>> {
+        if self.stack.contains(&(rule, start, end)) {
+            return None;
+        }
+
+        let grammar = self.grammar;
+        let mut res = None;
+        self.stack.push((rule, start, end));
+
+        for (alt, symbols) in grammar.alternatives[rule].iter().enumerate() {
+            let mut children = vec![];
+
+            if self.completed.contains(&(rule, alt, start, end))
+                && self.symbols(symbols, start, end, &mut children)
+            {
+                res = Some(children);
+                break;
+            }
+        }
+
+        self.stack.pop();
+        res
+    }
+
+    // derives the span of the message from the symbols, adds their nodes to `res`,
+    // nodes of auxiliary rules go right into it
+    fn symbols(&mut self, symbols: &[Symbol], pos: usize, end: usize, res: &mut Vec<Tree>) -> bool {
+        let (first, rest) = match symbols.split_first() {
+            Some(split) => split,
+            None => return pos == end,
+        };
+
+        let len = res.len();
+
+        match first {
+            Symbol::Term(term) => {
+                if self.message[pos..end].starts_with(term) {
+                    res.push(Tree::Term(String::from_utf8_lossy(term).into_owned()));
+                    if self.symbols(rest, pos + term.len(), end, res) {
+                        return true;
+                    }
+                }
+            }
+            Symbol::Rule(rule) => {
+                for mid in pos..=end {
+                    if !self.spans.contains(&(*rule, pos, mid)) {
+                        continue;
+                    }
+
+                    if let Some(children) = self.children(*rule, pos, mid) {
+                        match self.grammar.ids.get(*rule) {
+                            Some(id) => res.push(Tree::Rule(*id, children)),
+                            None => res.extend(children),
+                        }
+
+                        if self.symbols(rest, mid, end, res) {
+                            return true;
+                        }
+                    }
+
+                    res.truncate(len);
+                }
+            }
+        }
+
+        res.truncate(len);
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Grammar;
+    use crate::problem19::{parse, Mismatch, Tree};
+
+    #[test]
+    fn test_recursive() {
+        // balanced `a`s and `b`s around an optional `c`, left recursive list of them in 0
+        let rules = concat!(
+            "0: 1 | 0 1\n",
+            "1: 2 3 4 | 2 1 4\n",
+            "2: \"a\"\n",
+            "3: 6 | 5\n",
+            "4: \"b\"\n",
+            "5:\n",
+            "6: \"c\"",
+        );
+        let (rules, _) = parse(rules).unwrap();
+        let grammar = Grammar::new(&rules, 0).unwrap();
+
+        assert!(grammar.is_match(b"ab"));
+        assert!(grammar.is_match(b"acb"));
+        assert!(grammar.is_match(b"aacbbab"));
+        assert!(!grammar.is_match(b""));
+        assert!(!grammar.is_match(b"aab"));
+        assert!(!grammar.is_match(b"acbb"));
+
+        assert!(Grammar::new(&rules, 7).is_err());
+    }
+
+    #[test]
+    fn test_explain() {
+        let (rules, _) = parse("0: 1 2+ \"!\"?\n1: \"a\" | \"b\"\n2: 1 | \"c\"\n3: 3 | 1").unwrap();
+        let grammar = Grammar::new(&rules, 0).unwrap();
+
+        let tree = grammar.explain(b"acb!").unwrap();
+        assert_eq!(tree.text(), "acb!");
+        assert_eq!(
+            tree,
+            Tree::Rule(
+                0,
+                vec![
+                    Tree::Rule(1, vec![Tree::Term("a".to_owned())]),
+                    Tree::Rule(2, vec![Tree::Term("c".to_owned())]),
+                    Tree::Rule(2, vec![Tree::Rule(1, vec![Tree::Term("b".to_owned())])]),
+                    Tree::Term("!".to_owned()),
+                ]
+            )
+        );
+        assert_eq!(
+            tree.to_string(),
+            concat!(
+                "0 \"acb!\"\n",
+                "  1 \"a\"\n",
+                "    \"a\"\n",
+                "  2 \"c\"\n",
+                "    \"c\"\n",
+                "  2 \"b\"\n",
+                "    1 \"b\"\n",
+                "      \"b\"\n",
+                "  \"!\"\n",
+            )
+        );
+
+        // a rule deriving itself doesn't go round in circles
+        let tree = Grammar::new(&rules, 3).unwrap().explain(b"b").unwrap();
+        assert_eq!(tree.to_string(), "3 \"b\"\n  1 \"b\"\n    \"b\"\n");
+
+        let mismatch = grammar.explain(b"acx").unwrap_err();
+        assert_eq!(
+            mismatch,
+            Mismatch {
+                matched: 2,
+                rules: vec![1, 2],
+                terms: vec!["!".to_owned(), "a".to_owned(), "b".to_owned(), "c".to_owned()],
+                end: true,
+            }
+        );
+        assert_eq!(
+            mismatch.to_string(),
+            "expected rule 1, rule 2, \"!\", \"a\", \"b\", \"c\" or end of message after 2 bytes"
+        );
+
+        let mismatch = grammar.explain(b"a").unwrap_err();
+        assert_eq!((mismatch.matched, mismatch.end), (1, false));
+        assert_eq!(mismatch.rules, vec![1, 2]);
+        assert_eq!(grammar.explain(b"c").unwrap_err().matched, 0);
+    }
+}
@@ -1,324 +1,233 @@
 use std::collections::HashMap;
 use std::str;
 
-use failure::{format_err, Error};
-use utils::{result, RetTypes};
+use utils::{check_deadline, ParseError, ParseResult, ProblemResult};
 
-use prefix_tree::PrefixSet;
+use crate::problem::Problem;
 
-enum Node {
-    Single(Vec<usize>),
-    Multi(Vec<usize>, Vec<usize>),
-    Term(String),
-}
+mod earley;
+pub use earley::Grammar;
+
+mod syntax;
+pub use syntax::{parse_rule, Node};
+
+mod tree;
+pub use tree::{Mismatch, Tree};
 
 enum State {
     Rules,
     Input,
 }
 
-fn first_star(nodes_map: &HashMap<usize, Node>, input: &[Vec<u8>]) -> Vec<Vec<u8>> {
-    let p_tree = build_trie(&nodes_map, 0);
-
-    input
-        .iter()
-        .filter(|line| !p_tree.contains(line))
-        .cloned()
-        .collect::<Vec<Vec<u8>>>()
-}
-
-fn clone_from_node(nodes_map: &HashMap<usize, Node>, node_id: usize) -> HashMap<usize, Node> {
-    let mut res = HashMap::new();
+// number of messages matching rule 0
+fn count_matches(rules: &Rules, input: &[Vec<u8>]) -> ProblemResult<usize> {
+    let grammar = Grammar::new(rules, 0)?;
 
-    struct Ctx<'a> {
-        nodes_map: &'a HashMap<usize, Node>,
-        res: &'a mut HashMap<usize, Node>,
+    let mut count = 0;
+    for line in input {
+        check_deadline()?;
+        count += grammar.is_match(line) as usize;
     }
 
-    fn traverse(ctx: &mut Ctx, node_id: usize) {
-        match &ctx.nodes_map[&node_id] {
-            Node::Single(ids) => {
-                ctx.res.insert(node_id, Node::Single(ids.clone()));
-                for id in ids {
-                    traverse(ctx, *id);
-                }
-            }
-            Node::Multi(left, right) => {
-                ctx.res
-                    .insert(node_id, Node::Multi(left.clone(), right.clone()));
-                for id in left.iter().chain(right) {
-                    traverse(ctx, *id);
-                }
-            }
-            Node::Term(c) => {
-                ctx.res.insert(node_id, Node::Term(c.clone()));
-            }
-        }
-    }
+    Ok(count)
+}
 
-    traverse(
-        &mut Ctx {
-            nodes_map,
-            res: &mut res,
-        },
-        node_id,
-    );
+// rules the second star replaces
+pub const LOOPED_RULES: &str = "8: 42 | 42 8\n11: 42 31 | 42 11 31";
 
-    res
+fn second_star(rules: &Rules, input: &[Vec<u8>]) -> ProblemResult<usize> {
+    count_matches(&with_overrides(rules, LOOPED_RULES)?, input)
 }
 
-fn second_star(nodes_map: &HashMap<usize, Node>, input: &[Vec<u8>]) -> usize {
-    // replace 8 and 11 rules to their recursive variants:
+// rules by their numbers
+pub type Rules = HashMap<usize, Node>;
 
-    // 8: 42 | 42 8
-    // 11: 42 31 | 42 11 31
+// rules and messages
+pub type Messages = (Rules, Vec<Vec<u8>>);
 
-    let p_tree_42 = build_trie(&clone_from_node(nodes_map, 42), 42);
-    let p_tree_31 = build_trie(&clone_from_node(nodes_map, 31), 31);
+// empty or only a comment, such lines between rules are skipped
+fn is_blank(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || line.starts_with('#')
+}
 
-    fn match_tail(p_tree_31: &PrefixSet<u8>, line: &[u8], st: usize, depth: usize) -> bool {
-        if depth == 0 {
-            return st >= line.len();
-        }
+fn check_referenced(rules: &Rules, referenced: &[(usize, &str)]) -> ParseResult<()> {
+    match referenced.iter().find(|(id, _)| !rules.contains_key(id)) {
+        Some((_, item)) => Err(ParseError::at(item, "unknown rule")),
+        None => Ok(()),
+    }
+}
 
-        for end in st..=line.len() {
-            let slice = &line[st..end];
-            if p_tree_31.contains(slice) && match_tail(&p_tree_31, line, end, depth - 1) {
-                return true;
-            }
-        }
+// the rules with some of them replaced or added, one per line, e.g. `LOOPED_RULES`
+pub fn with_overrides(rules: &Rules, overrides: &str) -> ParseResult<Rules> {
+    let mut res = rules.clone();
+    let mut referenced = vec![];
 
-        false
+    for line in overrides.lines().filter(|line| !is_blank(line)) {
+        let (idx, node) = parse_rule(line, &mut referenced).map_err(|err| err.locate(overrides))?;
+        res.insert(idx, node);
     }
 
-    // 11: 42 31 | 42 11 31
-    fn apply_11(
-        p_tree_42: &PrefixSet<u8>,
-        p_tree_31: &PrefixSet<u8>,
-        line: &[u8],
-        st: usize,
-        depth: usize,
-    ) -> bool {
-        if st >= line.len() {
-            return false;
-        }
-
-        for end in st..line.len() {
-            let slice = &line[st..end];
+    check_referenced(&res, &referenced).map_err(|err| err.locate(overrides))?;
 
-            if p_tree_42.contains(slice) {
-                if match_tail(&p_tree_31, line, end, depth) {
-                    return true;
-                }
+    Ok(res)
+}
 
-                if apply_11(&p_tree_42, &p_tree_31, &line, end, depth + 1) {
-                    return true;
-                }
-            }
-        }
+pub fn parse(input_raw: &str) -> ParseResult<Messages> {
+    let mut nodes_map: HashMap<usize, Node> = HashMap::new();
+    let mut input: Vec<Vec<u8>> = vec![];
+    let mut state = State::Rules;
 
-        false
-    }
+    // rules referenced by other rules, all of them must be defined
+    let mut referenced: Vec<(usize, &str)> = vec![];
 
-    // 8: 42 | 42 8
-    fn apply_8(
-        p_tree_42: &PrefixSet<u8>,
-        p_tree_31: &PrefixSet<u8>,
-        line: &[u8],
-        st: usize,
-    ) -> bool {
-        if st >= line.len() {
-            return false;
+    for line in input_raw.lines() {
+        if line.trim().is_empty() {
+            state = State::Input;
+            continue;
         }
 
-        for end in st..line.len() {
-            let slice = &line[st..end];
-
-            // this means we've found one or more 8s rule
-            if p_tree_42.contains(slice) {
-
-                // rule 11 must go after rule 8 (cuz rule 0: 8 11)
-                if apply_11(&p_tree_42, &p_tree_31, &line, end, 1) {
-                    return true;
-                }
-
-                if apply_8(&p_tree_42, &p_tree_31, &line, end) {
-                    return true;
-                }
+        if let State::Rules = state {
+            if is_blank(line) {
+                continue;
             }
         }
 
-        false
-    }
-
-    let mut net = 0;
-    for line in input {
-        if apply_8(&p_tree_42, &p_tree_31, &line, 0) {
-            net += 1
+        if let State::Input = state {
+            input.push(Vec::from(line.trim().as_bytes()));
+            continue;
         }
-    }
-
-    net
-}
 
-fn gen_prod(xss: &[Vec<u8>], yss: &[Vec<u8>]) -> Vec<Vec<u8>> {
-    let mut res = vec![];
-    for xs in xss {
-        for ys in yss {
-            res.push(xs.iter().copied().chain(ys.iter().copied()).collect());
+        let (idx, node) = parse_rule(line, &mut referenced)?;
+        if nodes_map.insert(idx, node).is_some() {
+            return Err(ParseError::at(line, format!("rule {}: duplicate", idx)));
         }
     }
 
-    res
-}
-
-fn gen_multi_prod(inp: &[Vec<Vec<u8>>]) -> Vec<Vec<u8>> {
-    let mut res = inp[0].clone();
-    let mut idx = 1;
+    check_referenced(&nodes_map, &referenced)?;
 
-    while idx < inp.len() {
-        res = gen_prod(&res, &inp[idx]);
-        idx += 1;
+    if !nodes_map.contains_key(&0) {
+        return Err(ParseError::new("missing rule 0"));
     }
 
-    res
+    Ok((nodes_map, input))
 }
 
-fn build_trie(nodes_map: &HashMap<usize, Node>, root_id: usize) -> PrefixSet<u8> {
-    let mut resolved: HashMap<usize, PrefixSet<_>> = HashMap::new();
-
-    while resolved.len() < nodes_map.len() {
-        for (node_idx, node) in nodes_map {
-            if resolved.contains_key(node_idx) {
-                continue;
-            }
-
-            match node {
-                Node::Single(indices) => {
-                    if indices.iter().all(|idx| resolved.contains_key(idx)) {
-                        let mut trie = PrefixSet::new();
-                        let mut new_seq: Vec<Vec<Vec<u8>>> = vec![];
+pub struct Day19;
 
-                        for idx in indices {
-                            new_seq.push(resolved[idx].iter().collect::<Vec<Vec<u8>>>());
-                        }
+impl Problem for Day19 {
+    const DAY: usize = 19;
+    const TITLE: &'static str = "Monster Messages";
+    const INPUT: &'static str = include_str!("./input");
 
-                        for seq in gen_multi_prod(&new_seq) {
-                            trie.insert(seq);
-                        }
+    type Input = Messages;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-                        resolved.insert(*node_idx, trie);
-                    }
-                }
-
-                Node::Multi(left_ind, right_ind) => {
-                    let left = left_ind.iter().all(|idx| resolved.contains_key(idx));
-                    let right = right_ind.iter().all(|idx| resolved.contains_key(idx));
-
-                    if left && right {
-                        let mut trie = PrefixSet::new();
-
-                        for indices in &[left_ind, right_ind] {
-                            let mut new_seq: Vec<Vec<Vec<u8>>> = vec![];
-
-                            for idx in indices.iter() {
-                                new_seq.push(resolved[idx].iter().collect::<Vec<Vec<u8>>>());
-                            }
-
-                            for seq in gen_multi_prod(&new_seq) {
-                                trie.insert(seq);
-                            }
-                        }
-
-                        resolved.insert(*node_idx, trie);
-                    }
-                }
-
-                Node::Term(c) => {
-                    let mut trie = PrefixSet::new();
-                    trie.insert(c);
-                    resolved.insert(*node_idx, trie);
-                }
-            }
-        }
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
     }
 
-    let mut trie = PrefixSet::new();
-    for seq in resolved[&root_id].iter() {
-        trie.insert(seq);
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        let (nodes_map, messages) = input;
+        count_matches(nodes_map, messages)
     }
 
-    trie
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        let (nodes_map, messages) = input;
+        second_star(nodes_map, messages)
+    }
 }
 
-type ParseResult = Result<(HashMap<usize, Node>, Vec<Vec<u8>>), Error>;
+#[cfg(test)]
+mod tests {
+    use super::{count_matches, parse, second_star, with_overrides, Grammar, LOOPED_RULES};
+
+    const RAW_INPUT: &str = concat!(
+        "0: 4 1 5\n",
+        "1: 2 3 | 3 2\n",
+        "2: 4 4 | 5 5\n",
+        "3: 4 5 | 5 4\n",
+        "4: \"a\"\n",
+        "5: \"b\"\n",
+        "\n",
+        "ababbb\n",
+        "bababa\n",
+        "abbbab\n",
+        "aaabbb\n",
+        "aaaabbb",
+    );
 
-fn parse(input_raw: &str) -> ParseResult {
-    let mut nodes_map: HashMap<usize, Node> = HashMap::new();
-    let mut input: Vec<Vec<u8>> = vec![];
-    let mut state = State::Rules;
+    #[test]
+    fn test_matcher() {
+        let (rules, messages) = parse(RAW_INPUT).unwrap();
+        let grammar = Grammar::new(&rules, 0).unwrap();
 
-    // collect data into intermediate hashmap representation
+        let matched: Vec<bool> = messages.iter().map(|m| grammar.is_match(m)).collect();
+        assert_eq!(matched, vec![true, false, true, false, false]);
+        assert_eq!(count_matches(&rules, &messages).unwrap(), 2);
 
-    for line in input_raw.lines() {
-        if line.trim().is_empty() {
-            state = State::Input;
-        }
+        assert!(Grammar::new(&rules, 3).unwrap().is_match(b"ab"));
+        assert!(Grammar::new(&rules, 6).is_err());
+    }
 
-        if let State::Input = state {
-            input.push(Vec::from(line.trim().as_bytes()));
-            continue;
-        }
+    #[test]
+    fn test_overrides() {
+        let (rules, _) = parse("0: 8 11\n8: 42\n11: 42 31\n42: \"a\"\n31: \"b\"").unwrap();
+        let looped = with_overrides(&rules, LOOPED_RULES).unwrap();
+        let grammar = Grammar::new(&looped, 0).unwrap();
 
-        let mut splitter = line.split(':');
-
-        let idx = splitter
-            .next()
-            .ok_or_else(|| format_err!("error parsing line"))?
-            .parse::<usize>()?;
-        let tail = splitter.next().ok_or_else(|| format_err!("error parsing line"))?;
-
-        if tail.contains('a') {
-            nodes_map.insert(idx, Node::Term(String::from("a")));
-        } else if tail.contains('b') {
-            nodes_map.insert(idx, Node::Term(String::from("b")));
-        } else if tail.contains('|') {
-            splitter = tail.trim().split('|');
-            let mut indices = vec![];
-            for chunk in splitter {
-                indices.push(
-                    chunk
-                        .trim()
-                        .split_whitespace()
-                        .map(|item| item.parse::<usize>())
-                        .collect::<Result<Vec<_>, _>>()?,
-                );
-            }
-            nodes_map.insert(idx, Node::Multi(indices[0].clone(), indices[1].clone()));
-        } else {
-            let indices = tail
-                .trim()
-                .split_whitespace()
-                .map(|item| item.parse::<usize>())
-                .collect::<Result<Vec<_>, _>>()?;
-            nodes_map.insert(idx, Node::Single(indices.clone()));
-        }
-    }
+        assert!(grammar.is_match(b"aab"));
+        assert!(grammar.is_match(b"aaaabb"));
+        assert!(!grammar.is_match(b"abb"));
+        assert!(!grammar.is_match(b"ab"));
 
-    // build a graph from created map
-    Ok((nodes_map, input))
-}
+        let messages = vec![b"aab".to_vec(), b"aaaabb".to_vec(), b"abb".to_vec()];
+        assert_eq!(second_star(&rules, &messages).unwrap(), 2);
 
-#[allow(clippy::unnecessary_wraps)]
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let (nodes_map, input) = parse(input_raw)?;
+        // the same rules as before for the original grammar
+        let grammar = Grammar::new(&rules, 0).unwrap();
+        assert!(grammar.is_match(b"aab"));
+        assert!(!grammar.is_match(b"aaaabb"));
 
-    let inputs_left = first_star(&nodes_map, &input);
-    let first_start_res = input.len() - inputs_left.len();
+        // any rule can be replaced, rules it refers to must exist
+        let balanced = with_overrides(&rules, "0: 42 31 | 42 0 31").unwrap();
+        assert!(Grammar::new(&balanced, 0).unwrap().is_match(b"aaabbb"));
 
-    Ok(RetTypes::Usize(result(
-        Ok(first_start_res),
-        Ok(first_start_res + second_star(&nodes_map, &inputs_left)),
-    )))
+        let err = with_overrides(&rules, "8: 42\n0: 8 7").unwrap_err();
+        assert_eq!((err.line, err.column), (Some(2), Some(6)));
+        assert!(with_overrides(&rules, "8 42").is_err());
+    }
+
+    #[test]
+    fn test_extended_syntax() {
+        let rules = concat!(
+            "# any number of greetings, then a name\n",
+            "0: 1* 2 \"!\"?\n",
+            "1: (\"hi\" | \"hey\" | \"yo\") \", \"  # a greeting\n",
+            "2: (3 4?)+\n",
+            "3: \"bob\" | \"alice\" | \"\\\"x\\\"\"\n",
+            "4: \" \"\n",
+            "\n",
+            "bob\n",
+            "hi, yo, alice bob!\n",
+            "\"x\" bob\n",
+            "hi,bob\n",
+            "hey, !",
+        );
+        let (rules, messages) = parse(rules).unwrap();
+        let grammar = Grammar::new(&rules, 0).unwrap();
+
+        let matched: Vec<bool> = messages.iter().map(|m| grammar.is_match(m)).collect();
+        assert_eq!(matched, vec![true, true, true, false, false]);
+        assert_eq!(rules[&2].to_string(), "(3 4?)+");
+
+        let input = "0: 1\n# rule 1\n1: \"a\" (2 | \n2: \"b\"";
+        let err = parse(input).unwrap_err().locate(input);
+        assert_eq!((err.line, err.column), (Some(3), Some(8)));
+        assert_eq!(err.reason, "rule 1: unclosed paren");
+
+        let err = parse("0: 1\n1: \"a\"\n1: \"b\"").unwrap_err();
+        assert_eq!(err.reason, "rule 1: duplicate");
+    }
 }
@@ -0,0 +1,373 @@
+use std::fmt::{self, Display, Formatter};
+
+use utils::{parse_token, ParseError, ParseResult};
+
+// body of a rule
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    Rule(usize),
+    Term(String),
+    // every node one after another, an empty one matches an empty message
+    Seq(Vec<Node>),
+    // any one of the nodes
+    Alt(Vec<Node>),
+    Optional(Box<Node>),
+    ZeroOrMore(Box<Node>),
+    OneOrMore(Box<Node>),
+}
+
+impl Node {
+    // rule numbers the node refers to
+    pub fn referenced(&self) -> Vec<usize> {
+        let mut res = vec![];
+        self.collect_referenced(&mut res);
+        res
+    }
+
+    fn collect_referenced(&self, res: &mut Vec<usize>) {
+        match self {
+            Node::Rule(id) => res.push(*id),
+            Node::Term(_) => {}
+            Node::Seq(nodes) | Node::Alt(nodes) => {
+                nodes.iter().for_each(|node| node.collect_referenced(res))
+            }
+            Node::Optional(node) | Node::ZeroOrMore(node) | Node::OneOrMore(node) => {
+                node.collect_referenced(res)
+            }
+        }
+    }
+}
+
+// the same syntax the rules are parsed from
+impl Display for Node {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // alternatives inside of anything need parens, so do sequences under operators
+        let grouped = |node: &Node, seq_too: bool| match node {
+            Node::Alt(_) => format!("({})", node),
+            Node::Seq(nodes) if seq_too && nodes.len() != 1 => format!("({})", node),
+            _ => node.to_string(),
+        };
+
+        let join = |nodes: &[Node], sep: &str, seq_too: bool| {
+            nodes
+                .iter()
+                .map(|node| grouped(node, seq_too))
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+
+        match self {
+            Node::Rule(id) => write!(f, "{}", id),
+            Node::Term(term) => write!(f, "\"{}\"", term.replace('\\', "\\\\").replace('"', "\\\"")),
+            Node::Seq(nodes) => write!(f, "{}", join(nodes, " ", false)),
+            Node::Alt(nodes) => write!(
+                f,
+                "{}",
+                nodes
+                    .iter()
+                    .map(|node| node.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            ),
+            Node::Optional(node) => write!(f, "{}?", grouped(node, true)),
+            Node::ZeroOrMore(node) => write!(f, "{}*", grouped(node, true)),
+            Node::OneOrMore(node) => write!(f, "{}+", grouped(node, true)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Number,
+    Quoted,
+    Pipe,
+    Question,
+    Star,
+    Plus,
+    Open,
+    Close,
+}
+
+// a token along with its text in the input, for errors to point at
+#[derive(Clone, Copy, Debug)]
+struct Token<'a> {
+    kind: Kind,
+    text: &'a str,
+}
+
+struct Parser<'a> {
+    id: usize,
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    body: &'a str,
+    // rule numbers along with where they are mentioned
+    referenced: Vec<(usize, &'a str)>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, text: &str, reason: &str) -> ParseError {
+        ParseError::at(text, format!("rule {}: {}", self.id, reason))
+    }
+
+    // splits the body, everything after `#` outside of quotes is a comment
+    fn tokenize(&mut self) -> ParseResult<()> {
+        let body = self.body;
+        let mut chars = body.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            let kind = match c {
+                '#' => break,
+                c if c.is_whitespace() => continue,
+                '0'..='9' => {
+                    while let Some((_, '0'..='9')) = chars.peek() {
+                        chars.next();
+                    }
+                    Kind::Number
+                }
+                '"' => {
+                    let mut escaped = false;
+                    loop {
+                        match chars.next() {
+                            Some((_, '\\')) if !escaped => escaped = true,
+                            Some((_, '"')) if !escaped => break,
+                            Some(_) => escaped = false,
+                            None => return Err(self.error(&body[start..], "unclosed quote")),
+                        }
+                    }
+                    Kind::Quoted
+                }
+                '|' => Kind::Pipe,
+                '?' => Kind::Question,
+                '*' => Kind::Star,
+                '+' => Kind::Plus,
+                '(' => Kind::Open,
+                ')' => Kind::Close,
+                _ => {
+                    return Err(self.error(&body[start..start + c.len_utf8()], "unexpected symbol"))
+                }
+            };
+
+            let end = chars.peek().map(|(idx, _)| *idx).unwrap_or(body.len());
+            self.tokens.push(Token {
+                kind,
+                text: &body[start..end],
+            });
+        }
+
+        Ok(())
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    // alternatives separated by `|`
+    fn alt(&mut self) -> ParseResult<Node> {
+        let mut alts = vec![self.seq()?];
+
+        while let Some(Token {
+            kind: Kind::Pipe, ..
+        }) = self.peek()
+        {
+            self.pos += 1;
+            alts.push(self.seq()?);
+        }
+
+        Ok(if alts.len() == 1 {
+            alts.pop().unwrap()
+        } else {
+            Node::Alt(alts)
+        })
+    }
+
+    fn seq(&mut self) -> ParseResult<Node> {
+        let mut items = vec![];
+
+        while let Some(token) = self.peek() {
+            if token.kind == Kind::Pipe || token.kind == Kind::Close {
+                break;
+            }
+            items.push(self.item()?);
+        }
+
+        Ok(if items.len() == 1 {
+            items.pop().unwrap()
+        } else {
+            Node::Seq(items)
+        })
+    }
+
+    // an atom with any number of operators after it
+    fn item(&mut self) -> ParseResult<Node> {
+        let mut node = self.atom()?;
+
+        while let Some(token) = self.peek() {
+            node = match token.kind {
+                Kind::Question => Node::Optional(Box::new(node)),
+                Kind::Star => Node::ZeroOrMore(Box::new(node)),
+                Kind::Plus => Node::OneOrMore(Box::new(node)),
+                _ => break,
+            };
+            self.pos += 1;
+        }
+
+        Ok(node)
+    }
+
+    // a rule number, a quoted terminal or alternatives in parens
+    fn atom(&mut self) -> ParseResult<Node> {
+        let token = match self.peek() {
+            Some(token) => token,
+            None => return Err(self.error(&self.body[self.body.len()..], "unexpected end")),
+        };
+        self.pos += 1;
+
+        match token.kind {
+            Kind::Number => {
+                let id = parse_token(token.text, "rule number")?;
+                self.referenced.push((id, token.text));
+                Ok(Node::Rule(id))
+            }
+            Kind::Quoted => {
+                let term = unquote(&token.text[1..token.text.len() - 1]);
+                if term.is_empty() {
+                    return Err(self.error(token.text, "empty terminal"));
+                }
+                Ok(Node::Term(term))
+            }
+            Kind::Open => {
+                let node = self.alt()?;
+                match self.peek() {
+                    Some(Token {
+                        kind: Kind::Close, ..
+                    }) => {
+                        self.pos += 1;
+                        Ok(node)
+                    }
+                    _ => Err(self.error(token.text, "unclosed paren")),
+                }
+            }
+            _ => Err(self.error(token.text, "unexpected")),
+        }
+    }
+}
+
+fn unquote(s: &str) -> String {
+    let mut res = String::new();
+    let mut escaped = false;
+
+    for c in s.chars() {
+        if c == '\\' && !escaped {
+            escaped = true;
+        } else {
+            res.push(c);
+            escaped = false;
+        }
+    }
+
+    res
+}
+
+// a single `<rule number>: <rule>` line, where a rule is a sequence of rule numbers and quoted
+// terminals, `|` separates alternatives, `?`, `*` and `+` make the item before them optional
+// or repeated and parens group items, `#` starts a comment, e.g.
+// `8: 42+  # one or more of 42` or `3: "ab" (4 | 5)? | 6`
+pub fn parse_rule<'a>(
+    line: &'a str,
+    referenced: &mut Vec<(usize, &'a str)>,
+) -> ParseResult<(usize, Node)> {
+    let (id, body) = line
+        .split_once(':')
+        .ok_or_else(|| ParseError::at(line, "expected '<rule number>: <rule>', got"))?;
+    let id = parse_token::<usize>(id, "rule number")?;
+
+    let mut parser = Parser {
+        id,
+        tokens: vec![],
+        pos: 0,
+        body,
+        referenced: vec![],
+    };
+
+    parser.tokenize()?;
+    let node = parser.alt()?;
+
+    if let Some(token) = parser.peek() {
+        return Err(parser.error(token.text, "unmatched paren"));
+    }
+
+    referenced.extend(parser.referenced);
+
+    Ok((id, node))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_rule, Node};
+
+    fn rule(line: &str) -> Node {
+        parse_rule(line, &mut vec![]).unwrap().1
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        assert_eq!(
+            rule("0: 4 1 5"),
+            Node::Seq(vec![Node::Rule(4), Node::Rule(1), Node::Rule(5)])
+        );
+        assert_eq!(rule("4: \"a\""), Node::Term("a".to_owned()));
+        assert_eq!(rule("5:"), Node::Seq(vec![]));
+
+        let mut referenced = vec![];
+        let (id, node) = parse_rule(
+            "12: \"ab\\\"c\" 3 | (4 | 5)? 6* | 7+ # more to come",
+            &mut referenced,
+        )
+        .unwrap();
+
+        assert_eq!(id, 12);
+        assert_eq!(
+            node,
+            Node::Alt(vec![
+                Node::Seq(vec![Node::Term("ab\"c".to_owned()), Node::Rule(3)]),
+                Node::Seq(vec![
+                    Node::Optional(Box::new(Node::Alt(vec![Node::Rule(4), Node::Rule(5)]))),
+                    Node::ZeroOrMore(Box::new(Node::Rule(6))),
+                ]),
+                Node::OneOrMore(Box::new(Node::Rule(7))),
+            ])
+        );
+        assert_eq!(node.to_string(), "\"ab\\\"c\" 3 | (4 | 5)? 6* | 7+");
+        assert_eq!(rule(&format!("12: {}", node)), node);
+        assert_eq!(node.referenced(), vec![3, 4, 5, 6, 7]);
+        assert_eq!(
+            referenced.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            vec![3, 4, 5, 6, 7]
+        );
+
+        assert_eq!(rule("1: (2 3)+ 4").to_string(), "(2 3)+ 4");
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |line: &str| {
+            let err = parse_rule(line, &mut vec![]).unwrap_err().locate(line);
+            (err.column, err.to_string())
+        };
+
+        assert_eq!(
+            err("7: 1 % 2"),
+            (
+                Some(6),
+                "line 1, column 6: rule 7: unexpected symbol '%'".to_owned()
+            )
+        );
+        assert_eq!(err("7: (1 | 2").0, Some(4));
+        assert_eq!(err("7: 1 2)").0, Some(7));
+        assert_eq!(err("7: 1 \"ab").0, Some(6));
+        assert_eq!(err("7: \"\"").0, Some(4));
+        assert_eq!(err("7: 1 | ?").0, Some(8));
+        assert_eq!(err("7: 1 (").0, Some(6));
+        assert_eq!(err("x: 1").0, Some(1));
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt::{self, Display, Formatter};
+
+// how a message is derived from a rule, groups and operators of the rule add no nodes of their own,
+// whatever they match goes right into the rule
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Tree {
+    Rule(usize, Vec<Tree>),
+    Term(String),
+}
+
+impl Tree {
+    // the part of the message the node matches
+    pub fn text(&self) -> String {
+        match self {
+            Tree::Rule(_, children) => children.iter().map(|child| child.text()).collect(),
+            Tree::Term(term) => term.clone(),
+        }
+    }
+
+    fn write(&self, f: &mut Formatter<'_>, depth: usize) -> fmt::Result {
+        match self {
+            Tree::Rule(id, children) => {
+                writeln!(f, "{:indent$}{} {:?}", "", id, self.text(), indent = depth * 2)?;
+                for child in children {
+                    child.write(f, depth + 1)?;
+                }
+                Ok(())
+            }
+            Tree::Term(term) => writeln!(f, "{:indent$}{:?}", "", term, indent = depth * 2),
+        }
+    }
+}
+
+// a rule per line along with the text it matches, indented under its parent, e.g.
+// 0 "ab"
+//   1 "a"
+//     "a"
+//   "b"
+impl Display for Tree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+// why a message doesn't match
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    // length of the longest prefix some message matching the rule starts with
+    pub matched: usize,
+    // rules and terminals which could go on from there
+    pub rules: Vec<usize>,
+    pub terms: Vec<String>,
+    // the prefix itself matches the rule, so the message could end there
+    pub end: bool,
+}
+
+// e.g. `expected rule 4, rule 5 or "a" after 3 bytes`
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut expected: Vec<String> = self.rules.iter().map(|id| format!("rule {}", id)).collect();
+        expected.extend(self.terms.iter().map(|term| format!("{:?}", term)));
+        if self.end {
+            expected.push("end of message".to_owned());
+        }
+
+        let expected = match expected.split_last() {
+            None => "nothing".to_owned(),
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        };
+
+        write!(f, "expected {} after {} bytes", expected, self.matched)
+    }
+}
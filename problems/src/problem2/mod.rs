@@ -1,8 +1,9 @@
-use failure::Error;
-use utils::{result, split_by_lines, RetTypes};
+use utils::{parse_token, split_by_lines, ParseError, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
 
 #[derive(Debug)]
-struct PasswdItem {
+pub struct PasswdItem {
     first_pos: usize,
     second_pos: usize,
     symbol: char,
@@ -20,10 +21,10 @@ impl PasswdItem {
 
     fn is_valid_2(&self) -> bool {
         let tmp = self.passwd.chars().collect::<Vec<char>>();
-        let idx1 = self.first_pos - 1;
-        let idx2 = self.second_pos - 1;
-        (tmp[idx1] == self.symbol && tmp[idx2] != self.symbol)
-            || (tmp[idx1] != self.symbol && tmp[idx2] == self.symbol)
+        // positions start from 1, the ones past the end of a password never match
+        let first = tmp.get(self.first_pos - 1) == Some(&self.symbol);
+        let second = tmp.get(self.second_pos - 1) == Some(&self.symbol);
+        first != second
     }
 }
 
@@ -41,37 +42,73 @@ fn second_star(input: &[PasswdItem]) -> usize {
         .sum()
 }
 
-fn parse(input_raw: &str) -> Result<Vec<PasswdItem>, Error> {
-    let res: Vec<PasswdItem> = split_by_lines(input_raw, &|line: &str| {
-        let tmp = line.trim().replace(':', " ").replace('-', &" ");
-        let v = tmp.split_whitespace().collect::<Vec<&str>>();
+fn parse_pos(token: &str) -> ParseResult<usize> {
+    match parse_token(token, "position")? {
+        0 => Err(ParseError::at(token.trim(), "positions start from 1, got")),
+        pos => Ok(pos),
+    }
+}
+
+// line example: "1-3 a: abcde"
+fn parse(input_raw: &str) -> ParseResult<Vec<PasswdItem>> {
+    split_by_lines(input_raw, &|line: &str| {
+        let (policy, passwd) = line
+            .split_once(':')
+            .ok_or_else(|| ParseError::at(line, "expected '<policy>: <password>', got"))?;
+        let (positions, symbol) = policy
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| ParseError::at(policy, "expected '<from>-<to> <symbol>', got"))?;
+        let (first_pos, second_pos) = positions
+            .split_once('-')
+            .ok_or_else(|| ParseError::at(positions, "expected '<from>-<to>', got"))?;
+
+        let symbol = symbol.trim();
+        let mut chars = symbol.chars();
 
-        let (first_pos, second_pos, symbol, passwd) = (v[0], v[1], v[2], v[3]);
+        let symbol = match (chars.next(), chars.next()) {
+            (Some(c), None) => c,
+            _ => return Err(ParseError::at(symbol, "expected a single symbol, got")),
+        };
 
         Ok(PasswdItem {
-            first_pos: first_pos.parse::<usize>()?,
-            second_pos: second_pos.parse::<usize>()?,
-            symbol: symbol.trim().chars().collect::<Vec<char>>()[0],
+            first_pos: parse_pos(first_pos)?,
+            second_pos: parse_pos(second_pos)?,
+            symbol,
             passwd: passwd.trim().to_owned(),
         })
-    })?;
-
-    Ok(res)
+    })
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input = parse(input_raw)?;
+pub struct Day2;
+
+impl Problem for Day2 {
+    const DAY: usize = 2;
+    const TITLE: &'static str = "Password Philosophy";
+    const INPUT: &'static str = include_str!("./input");
+
+    type Input = Vec<PasswdItem>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
+    }
+
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        Ok(first_star(input))
+    }
 
-    Ok(RetTypes::Usize(result(
-        Ok(first_star(&input)),
-        Ok(second_star(&input)),
-    )))
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        Ok(second_star(input))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::parse;
+    use utils::ParseError;
+
     #[test]
     fn test_parse() {
         let res = parse(
@@ -83,12 +120,33 @@ mod tests {
 
         assert_eq!(res.len(), 3);
 
-        assert_eq!(res[0].is_valid_1(), true);
-        assert_eq!(res[1].is_valid_1(), false);
-        assert_eq!(res[2].is_valid_1(), true);
+        assert!(res[0].is_valid_1());
+        assert!(!res[1].is_valid_1());
+        assert!(res[2].is_valid_1());
+
+        assert!(res[0].is_valid_2());
+        assert!(!res[1].is_valid_2());
+        assert!(!res[2].is_valid_2());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = "1-3 a: abcde\n1-x b: cdefg";
+        let err = parse(input).err().unwrap().locate(input);
+        assert_eq!((err.line, err.column), (Some(2), Some(3)));
+        assert_eq!(err.text, "x");
+
+        let err = |input: &str| parse(input).err().map(|err: ParseError| err.reason);
+
+        assert_eq!(
+            err("1-3 a abcde"),
+            Some("expected '<policy>: <password>', got".to_owned())
+        );
+        assert_eq!(err("1-3 ab: abcde"), Some("expected a single symbol, got".to_owned()));
+        assert_eq!(err("0-3 a: abcde"), Some("positions start from 1, got".to_owned()));
 
-        assert_eq!(res[0].is_valid_2(), true);
-        assert_eq!(res[1].is_valid_2(), false);
-        assert_eq!(res[2].is_valid_2(), false);
+        // positions past the end of a password are fine
+        assert!(parse("1-30 a: abcde").unwrap()[0].is_valid_2());
+        assert!(!parse("10-30 a: abcde").unwrap()[0].is_valid_2());
     }
 }
@@ -1,5 +1,8 @@
-use failure::Error;
-use utils::{result, RetTypes};
+use std::convert::TryFrom;
+
+use utils::{Grid, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
 
 const SLOPES: &[Slope] = &[
     Slope { right: 1, down: 1 },
@@ -10,16 +13,19 @@ const SLOPES: &[Slope] = &[
 ];
 
 #[derive(Copy, Clone, Eq, PartialEq)]
-enum Cell {
+pub enum Cell {
     Empty,
     Obstacle,
 }
 
-impl From<char> for Cell {
-    fn from(c: char) -> Self {
+impl TryFrom<char> for Cell {
+    type Error = ();
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
         match c {
-            '#' => Cell::Obstacle,
-            _ => Cell::Empty,
+            '#' => Ok(Cell::Obstacle),
+            '.' => Ok(Cell::Empty),
+            _ => Err(()),
         }
     }
 }
@@ -29,32 +35,8 @@ struct Slope {
     down: usize,
 }
 
-// let's have some fun with const generics
-struct Board {
-    board: Vec<Cell>,
-    width: usize,
-    height: usize,
-}
-
-impl Board {
-    fn new(board: Vec<Cell>, width: usize, height: usize) -> Self {
-        Board {
-            board,
-            width,
-            height,
-        }
-    }
-
-    fn get_index(&self, row: usize, col: usize) -> usize {
-        row * self.width + (col % self.width)
-    }
-
-    #[inline(always)]
-    fn get_cell(&self, row: usize, col: usize) -> Cell {
-        let idx = self.get_index(row, col);
-        self.board[idx]
-    }
-}
+// the map repeats to the right
+pub type Board = Grid<Cell>;
 
 fn first_star(board: &Board, slope: &Slope) -> usize {
     let mut cur_col = 0;
@@ -62,8 +44,8 @@ fn first_star(board: &Board, slope: &Slope) -> usize {
 
     let mut trees_count = 0;
 
-    while cur_row < board.height {
-        if board.get_cell(cur_row, cur_col) == Cell::Obstacle {
+    while cur_row < board.height() {
+        if *board.get_wrapping(cur_row as isize, cur_col as isize) == Cell::Obstacle {
             trees_count += 1;
         }
 
@@ -78,44 +60,39 @@ fn second_star(board: &Board, slopes: &[Slope]) -> usize {
     Iterator::product(slopes.iter().map(|slope| first_star(board, slope)))
 }
 
-fn parse(input_raw: &[u8]) -> Board {
-    let mut field = Vec::new();
+fn parse(input_raw: &str) -> ParseResult<Board> {
+    Grid::parse(input_raw)
+}
 
-    let mut rows = 0;
-    let mut cols = 0;
+pub struct Day3;
 
-    for c in input_raw {
-        let c = *c as char;
+impl Problem for Day3 {
+    const DAY: usize = 3;
+    const TITLE: &'static str = "Toboggan Trajectory";
+    const INPUT: &'static str = include_str!("./input");
 
-        if c == '\n' {
-            rows += 1;
-            cols = 0;
-        } else {
-            field.push(Cell::from(c));
-            cols += 1;
-        }
-    }
+    type Input = Board;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    // rows + 1 because there is must be no '\n' at the end of file
-    Board::new(field, cols, rows + 1)
-}
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
+    }
 
-#[allow(clippy::unnecessary_wraps)]
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_bytes!("./input");
-    let board = parse(input_raw);
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        Ok(first_star(input, &Slope { right: 3, down: 1 }))
+    }
 
-    Ok(RetTypes::Usize(result(
-        Ok(first_star(&board, &Slope { right: 3, down: 1 })),
-        Ok(second_star(&board, SLOPES)),
-    )))
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        Ok(second_star(input, SLOPES))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{first_star, parse, second_star, Slope, SLOPES};
 
-    const RAW_INPUT: &[u8] = concat!(
+    const RAW_INPUT: &str = concat!(
         "..##.......\n",
         "#...#...#..\n",
         ".#....#..#.\n",
@@ -127,26 +104,29 @@ mod tests {
         "#.##...#...\n",
         "#...##....#\n",
         ".#..#...#.#"
-    )
-    .as_bytes();
+    );
 
     #[test]
     fn test_parse() {
-        let board = parse(RAW_INPUT);
+        let board = parse(RAW_INPUT).unwrap();
+
+        assert_eq!(board.width(), 11);
+        assert_eq!(board.height(), 11);
 
-        assert_eq!(board.width, 11);
-        assert_eq!(board.height, 11);
+        assert!(parse("..#\n.#").is_err());
+        assert!(parse("..#\n.o.").is_err());
+        assert!(parse("\n").is_err());
     }
 
     #[test]
     fn test_first() {
-        let board = parse(RAW_INPUT);
+        let board = parse(RAW_INPUT).unwrap();
         assert_eq!(first_star(&board, &Slope { right: 3, down: 1 }), 7);
     }
 
     #[test]
     fn test_second() {
-        let board = parse(RAW_INPUT);
+        let board = parse(RAW_INPUT).unwrap();
         assert_eq!(second_star(&board, SLOPES), 336);
     }
 }
@@ -1,10 +1,11 @@
-use failure::{format_err, Error};
-use utils::{result, ParseResult, RetTypes};
+use utils::{parse_token, ParseError, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
 
 const EYE_COLOR: &[&str] = &["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
 
 #[derive(Default)]
-struct Passport {
+pub struct Passport {
     byr: Option<usize>,  // (Birth Year)
     iyr: Option<usize>,  // (Issue Year)
     eyr: Option<usize>,  // (Expiration Year)
@@ -122,25 +123,26 @@ fn second_star(input: &[Passport]) -> usize {
 }
 
 fn parse_field(field: &str, pasp: &mut Passport) -> ParseResult<()> {
-    let parts: Vec<&str> = field.split(':').collect();
-    let (name, value) = (parts[0], parts[1]);
+    let (name, value) = field
+        .split_once(':')
+        .ok_or_else(|| ParseError::at(field, "expected '<name>:<value>', got"))?;
 
     match name {
-        "byr" => pasp.byr = Some(value.parse()?),
-        "iyr" => pasp.iyr = Some(value.parse()?),
-        "eyr" => pasp.eyr = Some(value.parse()?),
+        "byr" => pasp.byr = Some(parse_token(value, "birth year")?),
+        "iyr" => pasp.iyr = Some(parse_token(value, "issue year")?),
+        "eyr" => pasp.eyr = Some(parse_token(value, "expiration year")?),
         "hgt" => pasp.hgt = Some(String::from(value)),
         "hcl" => pasp.hcl = Some(String::from(value)),
         "ecl" => pasp.ecl = Some(String::from(value)),
         "pid" => pasp.pid = Some(String::from(value)),
-        "cid" => pasp.cid = Some(value.parse()?),
-        _ => return Err(format_err!("unknown field name '{}'", name)),
+        "cid" => pasp.cid = Some(parse_token(value, "country id")?),
+        _ => return Err(ParseError::at(name, "unknown field")),
     };
 
     Ok(())
 }
 
-fn parse(input_raw: &str) -> Result<Vec<Passport>, Error> {
+fn parse(input_raw: &str) -> ParseResult<Vec<Passport>> {
     let mut passports: Vec<Passport> = Vec::new();
     let mut cur_pasp: Passport = Passport::default();
 
@@ -161,14 +163,28 @@ fn parse(input_raw: &str) -> Result<Vec<Passport>, Error> {
     Ok(passports)
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input = parse(input_raw)?;
+pub struct Day4;
+
+impl Problem for Day4 {
+    const DAY: usize = 4;
+    const TITLE: &'static str = "Passport Processing";
+    const INPUT: &'static str = include_str!("./input");
+
+    type Input = Vec<Passport>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
+    }
+
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        Ok(first_star(input))
+    }
 
-    Ok(RetTypes::Usize(result(
-        Ok(first_star(&input)),
-        Ok(second_star(&input)),
-    )))
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        Ok(second_star(input))
+    }
 }
 
 #[cfg(test)]
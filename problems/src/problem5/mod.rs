@@ -1,5 +1,6 @@
-use failure::{format_err, Error};
-use utils::{result, split_by_lines, ProblemResult, RetTypes};
+use utils::{split_by_lines, ParseError, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
 
 const SEQ_LEN: usize = 10;
 const ROW_IDX: usize = 7;
@@ -32,12 +33,11 @@ fn split_right(int: Interval) -> Interval {
     }
 }
 
-fn find_all_seats(input: &[Vec<Dir>]) -> ProblemResult<Vec<usize>> {
+// directions must be already validated, see `parse_dir`
+fn find_all_seats(input: &[Vec<Dir>]) -> Vec<usize> {
     let mut seats = Vec::new();
 
     for steps in input {
-        assert_eq!(steps.len(), SEQ_LEN);
-
         let mut row = Interval {
             lower: 0f64,
             upper: 127f64,
@@ -51,16 +51,14 @@ fn find_all_seats(input: &[Vec<Dir>]) -> ProblemResult<Vec<usize>> {
             if idx < ROW_IDX {
                 // find a row
                 row = match step {
-                    Dir::Front => split_left(row),
-                    Dir::Back => split_right(row),
-                    _ => return Err(format_err!("wrong direction {:?}", step)),
+                    Dir::Front | Dir::Left => split_left(row),
+                    Dir::Back | Dir::Right => split_right(row),
                 }
             } else {
                 // find a column
                 col = match step {
-                    Dir::Left => split_left(col),
-                    Dir::Right => split_right(col),
-                    _ => return Err(format_err!("wrong direction {:?}", step)),
+                    Dir::Left | Dir::Front => split_left(col),
+                    Dir::Right | Dir::Back => split_right(col),
                 }
             }
         }
@@ -68,7 +66,18 @@ fn find_all_seats(input: &[Vec<Dir>]) -> ProblemResult<Vec<usize>> {
         seats.push(row.lower as usize * 8 + col.lower as usize);
     }
 
-    Ok(seats)
+    seats
+}
+
+// rows are chosen by the first ROW_IDX directions, columns by the rest of them
+fn parse_dir(idx: usize, c: char) -> Option<Dir> {
+    match (idx < ROW_IDX, c) {
+        (true, 'F') => Some(Dir::Front),
+        (true, 'B') => Some(Dir::Back),
+        (false, 'L') => Some(Dir::Left),
+        (false, 'R') => Some(Dir::Right),
+        _ => None,
+    }
 }
 
 fn first_star(input: &[usize]) -> usize {
@@ -85,41 +94,65 @@ fn second_star(input: &[usize]) -> usize {
     expected_sum - cur_sum
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input: Vec<Vec<Dir>> = split_by_lines(input_raw, &|line: &str| {
-        line.chars()
-            .map(|c| match c {
-                'F' => Ok(Dir::Front),
-                'B' => Ok(Dir::Back),
-                'L' => Ok(Dir::Left),
-                'R' => Ok(Dir::Right),
-                c => Err(format_err!(
-                    "Failed to parse input, unknown character '{}'",
-                    c
-                )),
-            })
-            .collect::<Result<Vec<Dir>, _>>()
-    })?;
-
-    let seats = find_all_seats(&input)?;
-
-    Ok(RetTypes::Usize(result(
-        Ok(first_star(&seats)),
-        Ok(second_star(&seats)),
-    )))
+pub struct Day5;
+
+impl Problem for Day5 {
+    const DAY: usize = 5;
+    const TITLE: &'static str = "Binary Boarding";
+    const INPUT: &'static str = include_str!("./input");
+
+    type Input = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        let input: Vec<Vec<Dir>> = split_by_lines(input_raw, &|line: &str| {
+            let line = line.trim();
+
+            if line.chars().count() != SEQ_LEN {
+                return Err(ParseError::at(
+                    line,
+                    format!("expected {} directions, got", SEQ_LEN),
+                ));
+            }
+
+            line.char_indices()
+                .enumerate()
+                .map(|(idx, (pos, c))| {
+                    parse_dir(idx, c).ok_or_else(|| {
+                        ParseError::at(&line[pos..pos + c.len_utf8()], "wrong direction")
+                    })
+                })
+                .collect()
+        })?;
+
+        if input.is_empty() {
+            return Err(ParseError::new("there are no boarding passes"));
+        }
+
+        Ok(find_all_seats(&input))
+    }
+
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        Ok(first_star(input))
+    }
+
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        Ok(second_star(input))
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use super::{find_all_seats, Dir};
+    use super::{find_all_seats, Day5, Dir};
+    use crate::problem::Problem;
 
     #[test]
     fn test_first() {
         // FBFBBFFRLR
         assert_eq!(
-            find_all_seats(&vec![vec![
+            find_all_seats(&[vec![
                 Dir::Front,
                 Dir::Back,
                 Dir::Front,
@@ -130,14 +163,13 @@ mod tests {
                 Dir::Right,
                 Dir::Left,
                 Dir::Right
-            ],])
-            .unwrap()[0],
+            ]])[0],
             357
         );
 
         // FFFBBBFRRR
         assert_eq!(
-            find_all_seats(&vec![vec![
+            find_all_seats(&[vec![
                 Dir::Front,
                 Dir::Front,
                 Dir::Front,
@@ -148,9 +180,19 @@ mod tests {
                 Dir::Right,
                 Dir::Right,
                 Dir::Right
-            ],])
-            .unwrap()[0],
+            ]])[0],
             119
         );
     }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Day5::parse("FBFBBFFRLR\nFFFBBBFRRR").unwrap(), vec![357, 119]);
+
+        let err = Day5::parse("FBFBBFFRLR\nFFFBBRFRRR").unwrap_err();
+        assert_eq!((err.text.as_str(), err.reason.as_str()), ("R", "wrong direction"));
+
+        assert!(Day5::parse("FBFBBFFRL").is_err());
+        assert!(Day5::parse("").is_err());
+    }
 }
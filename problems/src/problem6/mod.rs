@@ -1,6 +1,7 @@
-use failure::Error;
 use std::collections::HashSet;
-use utils::{result, GroupByEmptyLine, RetTypes};
+use utils::{GroupByEmptyLine, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
 
 fn first_star(groups: &[Vec<String>]) -> usize {
     let mut net = 0;
@@ -63,15 +64,28 @@ fn parse(input_raw: &str) -> Vec<Vec<String>> {
     groups
 }
 
-#[allow(clippy::unnecessary_wraps)]
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let groups = parse(input_raw);
+pub struct Day6;
+
+impl Problem for Day6 {
+    const DAY: usize = 6;
+    const TITLE: &'static str = "Custom Customs";
+    const INPUT: &'static str = include_str!("./input");
+
+    type Input = Vec<Vec<String>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        Ok(parse(input_raw))
+    }
+
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        Ok(first_star(input))
+    }
 
-    Ok(RetTypes::Usize(result(
-        Ok(first_star(&groups)),
-        Ok(second_star(&groups)),
-    )))
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        Ok(second_star(input))
+    }
 }
 
 #[cfg(test)]
@@ -86,13 +100,13 @@ mod tests {
 
     #[test]
     fn test_first_star() {
-        let groups = parse(&INPUT_RAW);
+        let groups = parse(INPUT_RAW);
         assert_eq!(first_star(&groups), 11);
     }
 
     #[test]
     fn test_second_star() {
-        let groups = parse(&INPUT_RAW);
+        let groups = parse(INPUT_RAW);
         assert_eq!(second_star(&groups), 6);
     }
 }
@@ -1,10 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use failure::Error;
-use utils::{result, RetTypes};
+use failure::format_err;
+use utils::{parse_token, ParseError, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
 
 #[derive(Debug)]
-struct BagNameCount {
+pub struct BagNameCount {
     name: String,
     count: usize,
 }
@@ -57,63 +59,168 @@ fn second_star_helper(
     (accum_bags, current_bags)
 }
 
-fn second_star(all: &HashMap<String, Vec<BagNameCount>>) -> usize {
-    let contents = all.get("shiny gold").unwrap();
+fn second_star(all: &HashMap<String, Vec<BagNameCount>>) -> ProblemResult<usize> {
+    let contents = all
+        .get("shiny gold")
+        .ok_or_else(|| format_err!("there is no rule for shiny gold bags"))?;
 
     let (total, remainder) = second_star_helper(all, contents);
 
     // -1 don't count ourselves
-    total + remainder - 1
+    Ok(total + remainder - 1)
 }
 
-fn parse(input_raw: &str) -> Result<HashMap<String, Vec<BagNameCount>>, Error> {
+fn parse(input_raw: &str) -> ParseResult<HashMap<String, Vec<BagNameCount>>> {
     let mut parsed = HashMap::new();
+    // names of bags with rules in the input order and the bags they contain, all of them
+    // must have their own rules
+    let mut names = vec![];
+    let mut contained: HashMap<&str, Vec<&str>> = HashMap::new();
 
-    for line in input_raw.split('\n') {
+    for line in input_raw.lines().filter(|line| !line.trim().is_empty()) {
         // input example: "dotted blue bags contain 3 wavy bronze bags, 5 clear tomato bags."
+        let (bag_name, bags_contains) = line.trim().split_once(" bags contain ").ok_or_else(|| {
+            ParseError::at(line, "expected '<color> bags contain <bags>.', got")
+        })?;
+
+        let items = parsed
+            .entry(bag_name.to_owned())
+            .or_insert_with(Vec::new);
+        names.push(bag_name);
+
+        // "3 wavy bronze bags, 5 clear tomato bags"
+        let bags_contains = bags_contains.trim_end_matches('.');
+        if bags_contains == "no other bags" {
+            continue;
+        }
 
-        // remove "bags" and the dot at the end of a line: "dotted blue contain 3 wavy bronze, 5 clear tomato."
-        let line = line.replace("bags", "").replace("bag", "").replace('.', "");
+        for bag in bags_contains.split(", ") {
+            // "3 wavy bronze bags"
+            let (sub_count, sub_name) = bag
+                .split_once(' ')
+                .ok_or_else(|| ParseError::at(bag, "expected '<count> <color> bags', got"))?;
+            let sub_name = sub_name
+                .strip_suffix(" bags")
+                .or_else(|| sub_name.strip_suffix(" bag"))
+                .ok_or_else(|| ParseError::at(bag, "expected '<count> <color> bags', got"))?;
+
+            items.push(BagNameCount {
+                name: sub_name.to_owned(),
+                count: parse_token(sub_count, "count of bags")?,
+            });
+
+            contained.entry(bag_name).or_default().push(sub_name);
+        }
+    }
 
-        // split by "contain" words
-        let parts: Vec<String> = line.split("contain").map(|item| item.into()).collect();
+    if let Some(name) = names
+        .iter()
+        .flat_map(|name| contained.get(name).into_iter().flatten())
+        .find(|name| !parsed.contains_key(**name))
+    {
+        return Err(ParseError::at(name, "there is no rule for bag"));
+    }
 
-        // bag_name = "dotted blue", bag_contains = "3 wavy bronze, 5 clear tomato"
-        let (bag_name, bags_contains): (String, &str) = (parts[0].trim().into(), parts[1].trim());
+    check_cycles(&names, &contained)?;
 
-        // ["3 way bronze", "5 clear tomato"]
-        let bags: Vec<String> = bags_contains
-            .split(',')
-            .map(|item| item.trim().into())
-            .collect();
+    Ok(parsed)
+}
 
-        for bag in bags {
-            let items = parsed.entry(bag_name.clone()).or_insert_with(Vec::new);
+// a bag can't end up inside of itself, points at the bag in the rule closing the cycle
+fn check_cycles(names: &[&str], contained: &HashMap<&str, Vec<&str>>) -> ParseResult<()> {
+    // bags on the current path, and those with everything inside of them checked
+    let mut on_path = HashSet::new();
+    let mut checked = HashSet::new();
 
-            if bag != "no other" {
-                let mut splitter = bag.splitn(2, ' ');
-                // 3
-                let sub_count = splitter.next().unwrap().parse::<usize>()?;
-                // way bronze
-                let sub_name = splitter.next().unwrap().into();
+    for name in names {
+        // bags along with the index of the next bag inside of them to go into
+        let mut stack = vec![(*name, 0)];
 
-                items.push(BagNameCount {
-                    name: sub_name,
-                    count: sub_count,
-                });
+        while let Some((bag, idx)) = stack.pop() {
+            if checked.contains(bag) {
+                continue;
+            }
+            on_path.insert(bag);
+
+            let inside = contained.get(bag).map(Vec::as_slice).unwrap_or_default();
+            match inside.get(idx) {
+                Some(next) if on_path.contains(next) => {
+                    return Err(ParseError::at(next, "cyclic containment of bag"));
+                }
+                Some(next) => {
+                    stack.push((bag, idx + 1));
+                    stack.push((*next, 0));
+                }
+                None => {
+                    on_path.remove(bag);
+                    checked.insert(bag);
+                }
             }
         }
     }
 
-    Ok(parsed)
+    Ok(())
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input = parse(input_raw)?;
+pub struct Day7;
+
+impl Problem for Day7 {
+    const DAY: usize = 7;
+    const TITLE: &'static str = "Handy Haversacks";
+    const INPUT: &'static str = include_str!("./input");
+
+    type Input = HashMap<String, Vec<BagNameCount>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    Ok(RetTypes::Usize(result(
-        Ok(first_star(&input)),
-        Ok(second_star(&input)),
-    )))
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
+    }
+
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        Ok(first_star(input))
+    }
+
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        second_star(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{first_star, parse, second_star};
+
+    const INPUT_RAW: &str = "light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+    #[test]
+    fn test_stars() {
+        let bags = parse(INPUT_RAW).unwrap();
+        assert_eq!(first_star(&bags), 4);
+        assert_eq!(second_star(&bags).unwrap(), 32);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = "shiny gold bags contain 1 dark red bag.\ndark red bags contain 1 shiny gold bag.";
+        let err = parse(input).unwrap_err().locate(input);
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 25: cyclic containment of bag 'shiny gold'"
+        );
+
+        let input = INPUT_RAW.replace("1 dark olive bag", "1 light red bag");
+        let err = parse(&input).unwrap_err().locate(&input);
+        assert_eq!((err.line, err.column), (Some(5), Some(27)));
+
+        let input = INPUT_RAW.replace("faded blue bags contain no other bags.", "");
+        assert!(parse(&input).is_err());
+    }
 }
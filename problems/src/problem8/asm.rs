@@ -0,0 +1,103 @@
+use utils::{parse_token, ParseError, ParseResult};
+
+use super::isa::{register_index, Op, Opcode, Operand, MAX_OPERANDS};
+
+fn assemble_line(line: &str) -> ParseResult<Op> {
+    let mut tokens = line
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty());
+
+    // there is at least one token, empty lines are skipped
+    let mnemonic = tokens.next().unwrap_or(line);
+    let code =
+        Opcode::from_mnemonic(mnemonic).ok_or_else(|| ParseError::at(mnemonic, "unknown op"))?;
+
+    let mut args = [0; MAX_OPERANDS];
+    let operands = code.instruction().operands;
+
+    for (arg, operand) in args.iter_mut().zip(operands) {
+        let token = tokens
+            .next()
+            .ok_or_else(|| ParseError::after(line, "missing argument"))?;
+
+        *arg = match operand {
+            Operand::Register => register_index(token)
+                .ok_or_else(|| ParseError::at(token, "wrong register"))?
+                as isize,
+            Operand::Value => parse_token(token, "argument")?,
+        };
+    }
+
+    if let Some(token) = tokens.next() {
+        return Err(ParseError::at(token, "unexpected argument"));
+    }
+
+    Ok(Op::new(code, &args[..operands.len()]))
+}
+
+// one instruction per line, operands are separated with spaces or commas, everything after `;`
+// is a comment, errors point at the line and column of the offending token
+pub fn assemble(source: &str) -> ParseResult<Vec<Op>> {
+    source
+        .lines()
+        .map(|line| line.split(';').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| assemble_line(line).map_err(|err| err.locate(source)))
+        .collect()
+}
+
+// the program back to the source `assemble` accepts, one instruction per line
+pub fn disassemble(program: &[Op]) -> String {
+    program.iter().map(|op| format!("{}\n", op)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assemble, disassemble};
+    use crate::problem8::Op;
+
+    #[test]
+    fn test_round_trip() {
+        let source = "set b,3 ; counter\n\nacc +2\nadd b -1\njnz b, -2\nout a\nhlt\n";
+        let program = assemble(source).unwrap();
+
+        assert_eq!(program.len(), 6);
+        assert_eq!(program[1], Op::acc(2));
+
+        let text = disassemble(&program);
+        assert_eq!(
+            text,
+            "set b, +3\nacc +2\nadd b, -1\njnz b, -2\nout a\nhlt\n"
+        );
+        assert_eq!(assemble(&text).unwrap(), program);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |source: &str| {
+            let err = assemble(source).unwrap_err();
+            (err.line, err.column, err.reason)
+        };
+
+        assert_eq!(
+            err("nop +0\nfoo +1"),
+            (Some(2), Some(1), "unknown op".to_owned())
+        );
+        assert_eq!(
+            err("acc +1\n\njmp"),
+            (Some(3), Some(4), "missing argument".to_owned())
+        );
+        assert_eq!(
+            err("set x, 1"),
+            (Some(1), Some(5), "wrong register".to_owned())
+        );
+        assert_eq!(
+            err("add a, 1q"),
+            (Some(1), Some(8), "wrong argument".to_owned())
+        );
+        assert_eq!(
+            err("  hlt 5 ; stop"),
+            (Some(1), Some(7), "unexpected argument".to_owned())
+        );
+    }
+}
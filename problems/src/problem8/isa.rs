@@ -0,0 +1,244 @@
+use std::fmt::{self, Display, Formatter};
+
+// registers are named from `a` on, `a` is the accumulator of the original console
+pub const REGISTERS: usize = 4;
+pub const MAX_OPERANDS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Register,
+    // signed immediate value, e.g. `+3` or `-12`
+    Value,
+}
+
+// what comes after an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Next,
+    // relative to the instruction itself
+    Jump(isize),
+    Halt,
+    // the result doesn't fit into a register, the instruction has changed nothing
+    Overflow,
+}
+
+// everything an instruction can change but the instruction pointer
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct State {
+    pub registers: [isize; REGISTERS],
+    pub output: Vec<isize>,
+}
+
+// an entry of the instruction set, register operands come to `exec` as register indices
+pub struct Instruction {
+    pub mnemonic: &'static str,
+    pub operands: &'static [Operand],
+    pub exec: fn(&mut State, &[isize; MAX_OPERANDS]) -> Flow,
+}
+
+use Operand::{Register, Value};
+
+// `f` of the register and `arg` goes into the register unless it overflows
+fn apply(state: &mut State, reg: usize, arg: isize, f: fn(isize, isize) -> Option<isize>) -> Flow {
+    match f(state.registers[reg], arg) {
+        Some(value) => {
+            state.registers[reg] = value;
+            Flow::Next
+        }
+        None => Flow::Overflow,
+    }
+}
+
+// the instruction set, a new instruction only needs a line here, its opcode is its index
+pub const INSTRUCTIONS: &[Instruction] = &[
+    Instruction {
+        mnemonic: "acc",
+        operands: &[Value],
+        exec: |state, args| apply(state, 0, args[0], isize::checked_add),
+    },
+    Instruction {
+        mnemonic: "jmp",
+        operands: &[Value],
+        exec: |_, args| Flow::Jump(args[0]),
+    },
+    Instruction {
+        mnemonic: "nop",
+        operands: &[Value],
+        exec: |_, _| Flow::Next,
+    },
+    Instruction {
+        mnemonic: "set",
+        operands: &[Register, Value],
+        exec: |state, args| {
+            state.registers[args[0] as usize] = args[1];
+            Flow::Next
+        },
+    },
+    Instruction {
+        mnemonic: "add",
+        operands: &[Register, Value],
+        exec: |state, args| apply(state, args[0] as usize, args[1], isize::checked_add),
+    },
+    Instruction {
+        mnemonic: "mul",
+        operands: &[Register, Value],
+        exec: |state, args| apply(state, args[0] as usize, args[1], isize::checked_mul),
+    },
+    // jumps if the register is zero
+    Instruction {
+        mnemonic: "jz",
+        operands: &[Register, Value],
+        exec: |state, args| match state.registers[args[0] as usize] {
+            0 => Flow::Jump(args[1]),
+            _ => Flow::Next,
+        },
+    },
+    // jumps if the register is anything but zero
+    Instruction {
+        mnemonic: "jnz",
+        operands: &[Register, Value],
+        exec: |state, args| match state.registers[args[0] as usize] {
+            0 => Flow::Next,
+            _ => Flow::Jump(args[1]),
+        },
+    },
+    Instruction {
+        mnemonic: "out",
+        operands: &[Register],
+        exec: |state, args| {
+            state.output.push(state.registers[args[0] as usize]);
+            Flow::Next
+        },
+    },
+    Instruction {
+        mnemonic: "hlt",
+        operands: &[],
+        exec: |_, _| Flow::Halt,
+    },
+];
+
+// index of an instruction in `INSTRUCTIONS`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Opcode(usize);
+
+pub const ACC: Opcode = Opcode(0);
+pub const JMP: Opcode = Opcode(1);
+pub const NOP: Opcode = Opcode(2);
+
+impl Opcode {
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        INSTRUCTIONS
+            .iter()
+            .position(|instruction| instruction.mnemonic == mnemonic)
+            .map(Opcode)
+    }
+
+    pub fn instruction(self) -> &'static Instruction {
+        &INSTRUCTIONS[self.0]
+    }
+}
+
+pub fn register_name(idx: usize) -> char {
+    (b'a' + idx as u8) as char
+}
+
+pub fn register_index(name: &str) -> Option<usize> {
+    match name.as_bytes() {
+        [c] if c.is_ascii_lowercase() && ((c - b'a') as usize) < REGISTERS => {
+            Some((c - b'a') as usize)
+        }
+        _ => None,
+    }
+}
+
+// a single instruction with its operands, unused operands are zeros
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Op {
+    pub code: Opcode,
+    pub args: [isize; MAX_OPERANDS],
+}
+
+impl Op {
+    pub fn new(code: Opcode, args: &[isize]) -> Self {
+        assert_eq!(args.len(), code.instruction().operands.len());
+
+        let mut op = Op {
+            code,
+            args: [0; MAX_OPERANDS],
+        };
+        op.args[..args.len()].copy_from_slice(args);
+
+        op
+    }
+
+    pub fn acc(arg: isize) -> Self {
+        Op::new(ACC, &[arg])
+    }
+
+    pub fn jmp(arg: isize) -> Self {
+        Op::new(JMP, &[arg])
+    }
+
+    pub fn nop(arg: isize) -> Self {
+        Op::new(NOP, &[arg])
+    }
+
+    pub fn exec(&self, state: &mut State) -> Flow {
+        (self.code.instruction().exec)(state, &self.args)
+    }
+}
+
+// the same way as in the source, e.g. `jmp -3` or `jnz b, +2`
+impl Display for Op {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let instruction = self.code.instruction();
+        write!(f, "{}", instruction.mnemonic)?;
+
+        for (idx, (operand, arg)) in instruction.operands.iter().zip(&self.args).enumerate() {
+            let sep = if idx == 0 { " " } else { ", " };
+
+            match operand {
+                Register => write!(f, "{}{}", sep, register_name(*arg as usize))?,
+                Value => write!(f, "{}{:+}", sep, arg)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{register_index, Flow, Op, Opcode, State};
+
+    #[test]
+    fn test_exec() {
+        let mut state = State::default();
+        let op = |text: &str, args: &[isize]| Op::new(Opcode::from_mnemonic(text).unwrap(), args);
+
+        assert_eq!(Op::acc(5).exec(&mut state), Flow::Next);
+        assert_eq!(op("set", &[1, 7]).exec(&mut state), Flow::Next);
+        assert_eq!(op("mul", &[1, -2]).exec(&mut state), Flow::Next);
+        assert_eq!(state.registers, [5, -14, 0, 0]);
+
+        assert_eq!(op("jz", &[2, -3]).exec(&mut state), Flow::Jump(-3));
+        assert_eq!(op("jnz", &[2, -3]).exec(&mut state), Flow::Next);
+        assert_eq!(op("out", &[1]).exec(&mut state), Flow::Next);
+        assert_eq!(op("hlt", &[]).exec(&mut state), Flow::Halt);
+        assert_eq!(state.output, vec![-14]);
+
+        // overflowing instructions leave registers as they are
+        assert_eq!(op("set", &[1, isize::MAX]).exec(&mut state), Flow::Next);
+        assert_eq!(op("add", &[1, 1]).exec(&mut state), Flow::Overflow);
+        assert_eq!(op("mul", &[1, -2]).exec(&mut state), Flow::Overflow);
+        assert_eq!(Op::acc(isize::MIN).exec(&mut state), Flow::Next);
+        assert_eq!(Op::acc(-6).exec(&mut state), Flow::Overflow);
+        assert_eq!(state.registers, [isize::MIN + 5, isize::MAX, 0, 0]);
+
+        assert_eq!(op("add", &[3, 2]).to_string(), "add d, +2");
+        assert_eq!(Op::nop(0).to_string(), "nop +0");
+        assert!(Opcode::from_mnemonic("xyz").is_none());
+        assert_eq!(register_index("c"), Some(2));
+        assert_eq!(register_index("e"), None);
+    }
+}
@@ -1,123 +1,253 @@
-use std::collections::HashSet;
+use failure::format_err;
+use utils::{ParseResult, ProblemResult};
 
-use failure::{format_err, Error};
-use utils::{result, split_by_lines, ProblemResult, RetTypes};
+use crate::problem::Problem;
 
-#[derive(Debug)]
-enum Op {
-    Acc(isize),
-    Jmp(isize),
-    Nop(isize),
-}
+mod isa;
+pub use isa::{
+    register_name, Flow, Instruction, Op, Opcode, Operand, State, ACC, INSTRUCTIONS, JMP, NOP,
+    REGISTERS,
+};
+
+mod asm;
+pub use asm::{assemble, disassemble};
 
+mod vm;
+pub use vm::{Event, TraceEntry, Vm, TRACE_LIMIT};
+
+// runs programs of the handheld console, keeps the accumulator of the last run
 #[derive(Default)]
-struct Computer {
+pub struct Computer {
     acc: isize,
 }
 
-type IsLoop = bool;
-
 impl Computer {
-    fn run(&mut self, program: &[Op]) -> IsLoop {
-        let mut line_numbers = HashSet::new();
-        let mut ip = 0isize;
+    pub fn new() -> Self {
+        Computer::default()
+    }
 
-        self.acc = 0;
+    pub fn acc(&self) -> isize {
+        self.acc
+    }
 
-        while ip < program.len() as isize {
-            if line_numbers.contains(&ip) {
-                return true;
-            }
+    // runs until the program either stops or is about to execute some instruction twice,
+    // returns why it has stopped
+    pub fn run(&mut self, program: &[Op]) -> Event {
+        let mut vm = Vm::new(program);
+        let event = vm.resume();
 
-            let op = &program[ip as usize];
-            line_numbers.insert(ip);
+        self.acc = vm.acc();
 
-            match op {
-                Op::Acc(arg) => {
-                    self.acc += arg;
-                }
-                Op::Jmp(arg) => {
-                    ip += arg;
-                    continue;
-                }
-                Op::Nop(_) => {}
-            }
-            ip += 1;
-        }
+        event
+    }
+}
+
+// accumulator right before the program repeats itself, or once it has stopped on its own
+fn first_star(input: &[Op]) -> ProblemResult<isize> {
+    let mut c = Computer::new();
 
-        false
+    match c.run(input) {
+        Event::Loop(_) | Event::Terminated | Event::Halted => Ok(c.acc()),
+        event => Err(format_err!("{}", event)),
     }
 }
 
-fn first_star(input: &[Op]) -> isize {
-    let mut c = Computer::default();
-    c.run(&input);
+// the instruction with jmp and nop swapped, none for acc
+fn flipped(op: Op) -> Option<Op> {
+    match op.code {
+        JMP => Some(Op::nop(op.args[0])),
+        NOP => Some(Op::jmp(op.args[0])),
+        _ => None,
+    }
+}
 
-    c.acc
+// index of the instruction executed after the one at `ip`, if it is inside the program
+// or right after its end
+fn next_ip(program: &[Op], ip: usize, op: Op) -> Option<usize> {
+    let next = match op.code {
+        JMP => (ip as isize).checked_add(op.args[0])?,
+        _ => ip as isize + 1,
+    };
+
+    if next >= 0 && next as usize <= program.len() {
+        Some(next as usize)
+    } else {
+        None
+    }
 }
 
-fn switch_op_at_idx(input: &mut [Op], idx: usize) -> bool {
-    match input[idx] {
-        Op::Jmp(arg) => {
-            input[idx] = Op::Nop(arg);
-            true
-        }
-        Op::Nop(arg) => {
-            input[idx] = Op::Jmp(arg);
-            true
+// result of fixing the corrupted instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repair {
+    // index of the flipped instruction and what it has become
+    pub patched: usize,
+    pub op: Op,
+    // accumulator of the fixed program once it terminates
+    pub acc: isize,
+}
+
+// flips a single jmp or nop so the program terminates right after its last instruction:
+// every instruction which leads to the end is found walking jumps backwards from it,
+// then the first one on the original path whose flipped version lands there gets patched
+pub fn repair(program: &[Op]) -> ProblemResult<Repair> {
+    if let Some(op) = program.iter().find(|op| ![ACC, JMP, NOP].contains(&op.code)) {
+        return Err(format_err!(
+            "only programs of acc, jmp and nop can be repaired, found '{}'",
+            op
+        ));
+    }
+
+    let len = program.len();
+
+    // instructions every index is reached from, the end of the program included
+    let mut sources = vec![vec![]; len + 1];
+    for (ip, op) in program.iter().enumerate() {
+        if let Some(next) = next_ip(program, ip, *op) {
+            sources[next].push(ip);
         }
-        _ => false,
     }
-}
 
-fn second_star(input: &mut [Op]) -> ProblemResult<isize> {
-    let mut c = Computer::default();
+    let mut terminates = vec![false; len + 1];
+    let mut stack = vec![len];
+    terminates[len] = true;
 
-    for idx in 0..input.len() {
-        if c.run(&input) {
-            if !switch_op_at_idx(input, idx) {
-                // we can't switch 'acc'
-                continue;
+    while let Some(ip) = stack.pop() {
+        for source in &sources[ip] {
+            if !terminates[*source] {
+                terminates[*source] = true;
+                stack.push(*source);
             }
+        }
+    }
 
-            if !c.run(&input) {
-                // no loop, we've fixed the program
-                return Ok(c.acc);
-            }
+    if terminates[0] {
+        return Err(format_err!("the program terminates already"));
+    }
+
+    let mut visited = vec![false; len];
+    let mut ip = 0;
+
+    while ip < len && !visited[ip] {
+        visited[ip] = true;
+
+        if let Some(op) = flipped(program[ip]) {
+            if next_ip(program, ip, op).is_some_and(|next| terminates[next]) {
+                let mut fixed = program.to_vec();
+                fixed[ip] = op;
+
+                // the patched path is known to reach the end, but it may still overflow
+                let mut c = Computer::new();
+                match c.run(&fixed) {
+                    Event::Terminated => {}
+                    event => {
+                        return Err(format_err!(
+                            "the program with {} at {} doesn't terminate, {}",
+                            op,
+                            ip,
+                            event
+                        ))
+                    }
+                }
 
-            // restore previous state for the given index
-            switch_op_at_idx(input, idx);
+                return Ok(Repair {
+                    patched: ip,
+                    op,
+                    acc: c.acc(),
+                });
+            }
         }
+
+        ip = match next_ip(program, ip, program[ip]) {
+            Some(next) => next,
+            None => break,
+        };
     }
 
     Err(format_err!("solution not found"))
 }
 
-fn parse(input_raw: &str) -> Result<Vec<Op>, Error> {
-    let res: Result<Vec<Op>, _> = split_by_lines(input_raw, &|line: &str| {
-        let mut splitter = line.splitn(2, ' ');
-        // op
-        let op = splitter.next().unwrap();
-        // argument
-        let arg = splitter.next().unwrap().parse::<isize>()?;
-        match op {
-            "acc" => Ok(Op::Acc(arg)),
-            "jmp" => Ok(Op::Jmp(arg)),
-            "nop" => Ok(Op::Nop(arg)),
-            _ => Err(format_err!("unknown op '{}'", op)),
-        }
-    });
+pub fn parse(input_raw: &str) -> ParseResult<Vec<Op>> {
+    assemble(input_raw)
+}
+
+pub struct Day8;
+
+impl Problem for Day8 {
+    const DAY: usize = 8;
+    const TITLE: &'static str = "Handheld Halting";
+    const INPUT: &'static str = include_str!("./input");
+
+    type Input = Vec<Op>;
+    type Answer1 = isize;
+    type Answer2 = isize;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        parse(input_raw)
+    }
+
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        first_star(input)
+    }
 
-    res
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        Ok(repair(input)?.acc)
+    }
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let mut input = parse(input_raw)?;
+#[cfg(test)]
+mod tests {
+    use super::{first_star, parse, repair, Computer, Event, Op, Repair};
+
+    const RAW_INPUT: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    #[test]
+    fn test_run() {
+        let program = parse(RAW_INPUT).unwrap();
+        assert_eq!(program[2], Op::jmp(4));
 
-    let fst = first_star(&input);
-    // just to be sure I won't brake anything doing part two
-    assert_eq!(fst, 1337);
+        let mut c = Computer::new();
+        assert_eq!(c.run(&program), Event::Loop(1));
+        assert_eq!(c.acc(), 5);
 
-    Ok(RetTypes::Isize(result(Ok(fst), second_star(&mut input))))
+        assert_eq!(c.run(&[Op::acc(2), Op::nop(-1)]), Event::Terminated);
+        assert_eq!(c.acc(), 2);
+
+        assert_eq!(first_star(&program).unwrap(), 5);
+        let overflow = parse("acc +9223372036854775807\nacc +1\njmp -2").unwrap();
+        assert_eq!(
+            first_star(&overflow).unwrap_err().to_string(),
+            "instruction 1 overflows a register"
+        );
+        assert!(first_star(&[Op::jmp(-1)]).is_err());
+
+        assert_eq!(program[4].to_string(), "jmp -3");
+        assert_eq!(program[0].to_string(), "nop +0");
+    }
+
+    #[test]
+    fn test_repair() {
+        let program = parse(RAW_INPUT).unwrap();
+        assert_eq!(
+            repair(&program).unwrap(),
+            Repair {
+                patched: 7,
+                op: Op::nop(-4),
+                acc: 8
+            }
+        );
+
+        assert!(repair(&[Op::jmp(0), Op::jmp(-1)]).is_err());
+        assert!(repair(&[Op::acc(1), Op::nop(5)]).is_err());
+
+        // the first jmp turned into nop leads out of the program, only the last one works
+        let program = parse("jmp +2\njmp +5\nacc +1\njmp -1").unwrap();
+        assert_eq!(repair(&program).unwrap().patched, 3);
+
+        // the patched program reaches the end only if it doesn't overflow on the way
+        let program = parse("acc +9223372036854775807\nacc +1\njmp -2").unwrap();
+        assert_eq!(
+            repair(&program).unwrap_err().to_string(),
+            "the program with nop -2 at 2 doesn't terminate, instruction 1 overflows a register"
+        );
+    }
 }
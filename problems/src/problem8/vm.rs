@@ -0,0 +1,422 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::{self, Display, Formatter},
+};
+
+use super::{Flow, Op, State};
+
+// why the machine has stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    // a single instruction has been executed, nothing special happened
+    Stepped,
+    // the instruction pointer is right after the last instruction
+    Terminated,
+    // the instruction pointer is somewhere else outside of the program
+    OutOfBounds(isize),
+    // a halt instruction has been executed
+    Halted,
+    // the instruction at the given index would overflow a register, it can't be executed
+    Overflow(usize),
+    // the instruction at the given index is about to be executed for the second time
+    Loop(usize),
+    Breakpoint(usize),
+    // the instruction at `ip` has changed the accumulator while it is watched
+    AccChanged { ip: usize, before: isize, after: isize },
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Stepped => write!(f, "stepped"),
+            Event::Terminated => write!(f, "program terminated"),
+            Event::OutOfBounds(ip) => write!(f, "jumped out of the program to {}", ip),
+            Event::Halted => write!(f, "program halted"),
+            Event::Overflow(ip) => write!(f, "instruction {} overflows a register", ip),
+            Event::Loop(ip) => write!(f, "instruction {} is about to be executed twice", ip),
+            Event::Breakpoint(ip) => write!(f, "breakpoint at {}", ip),
+            Event::AccChanged { ip, before, after } => {
+                write!(f, "acc changed from {} to {} at {}", before, after, ip)
+            }
+        }
+    }
+}
+
+// a single executed instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub ip: usize,
+    pub op: Op,
+    pub acc_before: isize,
+    pub acc_after: isize,
+}
+
+impl Display for TraceEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>5}: {:<10} acc {} -> {}",
+            self.ip,
+            self.op.to_string(),
+            self.acc_before,
+            self.acc_after
+        )
+    }
+}
+
+// number of the latest executed instructions the trace keeps
+pub const TRACE_LIMIT: usize = 1000;
+
+// the handheld console, executes its program one instruction at a time
+pub struct Vm {
+    program: Vec<Op>,
+    ip: isize,
+    state: State,
+    halted: bool,
+    steps: usize,
+    breakpoints: HashSet<usize>,
+    watch_acc: bool,
+    tracing: bool,
+    // empty if tracing is off, older entries make way for new ones past `TRACE_LIMIT`
+    trace: VecDeque<TraceEntry>,
+}
+
+impl Vm {
+    pub fn new(program: &[Op]) -> Self {
+        Vm {
+            program: program.to_vec(),
+            ip: 0,
+            state: State::default(),
+            halted: false,
+            steps: 0,
+            breakpoints: HashSet::new(),
+            watch_acc: false,
+            tracing: false,
+            trace: VecDeque::new(),
+        }
+    }
+
+    pub fn program(&self) -> &[Op] {
+        &self.program
+    }
+
+    pub fn ip(&self) -> isize {
+        self.ip
+    }
+
+    // the first register
+    pub fn acc(&self) -> isize {
+        self.state.registers[0]
+    }
+
+    pub fn registers(&self) -> &[isize] {
+        &self.state.registers
+    }
+
+    // values printed by the program so far
+    pub fn output(&self) -> &[isize] {
+        &self.state.output
+    }
+
+    // number of instructions executed since the last reset
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    // the instruction about to be executed, none once the machine has left the program
+    pub fn current(&self) -> Option<Op> {
+        self.index(self.ip).map(|ip| self.program[ip])
+    }
+
+    // back to the very start, breakpoints, watch and tracing stay as they are
+    pub fn reset(&mut self) {
+        self.ip = 0;
+        self.state = State::default();
+        self.halted = false;
+        self.steps = 0;
+        self.trace.clear();
+    }
+
+    // returns false if there is no instruction at the index
+    pub fn add_breakpoint(&mut self, ip: usize) -> bool {
+        if ip < self.program.len() {
+            self.breakpoints.insert(ip);
+            true
+        } else {
+            false
+        }
+    }
+
+    // returns false if there was no breakpoint at the index
+    pub fn remove_breakpoint(&mut self, ip: usize) -> bool {
+        self.breakpoints.remove(&ip)
+    }
+
+    pub fn breakpoints(&self) -> Vec<usize> {
+        let mut breakpoints: Vec<_> = self.breakpoints.iter().copied().collect();
+        breakpoints.sort_unstable();
+        breakpoints
+    }
+
+    pub fn set_watch_acc(&mut self, watch: bool) {
+        self.watch_acc = watch;
+    }
+
+    pub fn watch_acc(&self) -> bool {
+        self.watch_acc
+    }
+
+    // turning tracing off drops the trace collected so far
+    pub fn set_tracing(&mut self, tracing: bool) {
+        self.tracing = tracing;
+        if !tracing {
+            self.trace.clear();
+        }
+    }
+
+    pub fn tracing(&self) -> bool {
+        self.tracing
+    }
+
+    // the oldest entry goes first
+    pub fn trace(&self) -> &VecDeque<TraceEntry> {
+        &self.trace
+    }
+
+    fn index(&self, ip: isize) -> Option<usize> {
+        if ip >= 0 && (ip as usize) < self.program.len() {
+            Some(ip as usize)
+        } else {
+            None
+        }
+    }
+
+    // the machine can't go on from here
+    fn outside(&self) -> Option<Event> {
+        if self.halted {
+            return Some(Event::Halted);
+        }
+
+        match self.index(self.ip) {
+            Some(_) => None,
+            None if self.ip == self.program.len() as isize => Some(Event::Terminated),
+            None => Some(Event::OutOfBounds(self.ip)),
+        }
+    }
+
+    // executes the current instruction, even if it has been executed before
+    // or has a breakpoint on it
+    pub fn step(&mut self) -> Event {
+        if let Some(event) = self.outside() {
+            return event;
+        }
+
+        let ip = self.ip as usize;
+        let op = self.program[ip];
+        let acc_before = self.acc();
+
+        match op.exec(&mut self.state) {
+            Flow::Next => self.ip += 1,
+            // a jump past the range of `isize` is just as much out of bounds
+            Flow::Jump(offset) => self.ip = self.ip.saturating_add(offset),
+            Flow::Halt => self.halted = true,
+            Flow::Overflow => return Event::Overflow(ip),
+        }
+
+        let acc_after = self.acc();
+
+        self.steps += 1;
+
+        if self.tracing {
+            if self.trace.len() == TRACE_LIMIT {
+                self.trace.pop_front();
+            }
+            self.trace.push_back(TraceEntry {
+                ip,
+                op,
+                acc_before,
+                acc_after,
+            });
+        }
+
+        if self.watch_acc && acc_before != acc_after {
+            Event::AccChanged {
+                ip,
+                before: acc_before,
+                after: acc_after,
+            }
+        } else if self.halted {
+            Event::Halted
+        } else {
+            Event::Stepped
+        }
+    }
+
+    // runs until the program halts or leaves its bounds, is about to repeat an instruction
+    // executed since the resume, hits a breakpoint or changes the watched accumulator,
+    // the current instruction is always executed, so resuming from a breakpoint doesn't stop
+    // right away and resuming from a loop goes one more round
+    pub fn resume(&mut self) -> Event {
+        let mut visited = HashSet::new();
+
+        loop {
+            if let Some(event) = self.outside() {
+                return event;
+            }
+
+            let ip = self.ip as usize;
+            if !visited.insert(ip) {
+                return Event::Loop(ip);
+            }
+
+            if visited.len() > 1 && self.breakpoints.contains(&ip) {
+                return Event::Breakpoint(ip);
+            }
+
+            let event = self.step();
+            if event != Event::Stepped {
+                return event;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, Vm, TRACE_LIMIT};
+    use crate::problem8::{assemble, parse, Op};
+
+    const RAW_INPUT: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    #[test]
+    fn test_step() {
+        let mut vm = Vm::new(&parse(RAW_INPUT).unwrap());
+
+        assert_eq!(vm.current(), Some(Op::nop(0)));
+        assert_eq!(vm.step(), Event::Stepped);
+        assert_eq!(vm.step(), Event::Stepped);
+        assert_eq!((vm.ip(), vm.acc(), vm.steps()), (2, 1, 2));
+
+        assert_eq!(vm.resume(), Event::Loop(2));
+        assert_eq!(vm.acc(), 6);
+        assert_eq!(vm.resume(), Event::Loop(2));
+        assert_eq!(vm.acc(), 11);
+
+        // stepping goes on regardless of the loop
+        assert_eq!(vm.step(), Event::Stepped);
+        assert_eq!(vm.ip(), 6);
+
+        let mut vm = Vm::new(&[Op::acc(2), Op::jmp(-5)]);
+        assert_eq!(vm.resume(), Event::OutOfBounds(-4));
+        assert_eq!(vm.step(), Event::OutOfBounds(-4));
+
+        let mut vm = Vm::new(&[Op::nop(0), Op::jmp(isize::MAX)]);
+        assert_eq!(vm.resume(), Event::OutOfBounds(isize::MAX));
+
+        let mut vm = Vm::new(&[Op::acc(2), Op::nop(7)]);
+        assert_eq!(vm.resume(), Event::Terminated);
+        vm.reset();
+        assert_eq!((vm.ip(), vm.acc(), vm.steps()), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_extended() {
+        let program = assemble("set b, 3\nacc +2\nadd b, -1\njnz b, -2\nout a\nhlt\nacc +1").unwrap();
+        let mut vm = Vm::new(&program);
+
+        // every resume goes one more round
+        assert_eq!(vm.resume(), Event::Loop(1));
+        assert_eq!(vm.registers(), &[2, 2, 0, 0]);
+        assert_eq!(vm.resume(), Event::Loop(1));
+        assert_eq!(vm.registers(), &[4, 1, 0, 0]);
+        assert_eq!(vm.resume(), Event::Halted);
+        assert_eq!(vm.step(), Event::Halted);
+        assert_eq!(vm.registers(), &[6, 0, 0, 0]);
+        assert_eq!(vm.output(), &[6]);
+        assert_eq!(vm.steps(), 12);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let program = assemble("set b, 1\nmul b, +1000000000\njmp -1").unwrap();
+        let mut vm = Vm::new(&program);
+
+        let mut event = vm.step();
+        while event == Event::Stepped {
+            event = vm.step();
+        }
+
+        // the third multiplication overflows, the machine stays right before it
+        assert_eq!(event, Event::Overflow(1));
+        assert_eq!((vm.ip(), vm.steps()), (1, 5));
+        assert_eq!(vm.registers(), &[0, 1_000_000_000_000_000_000, 0, 0]);
+        assert_eq!(vm.resume(), Event::Overflow(1));
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut vm = Vm::new(&parse(RAW_INPUT).unwrap());
+
+        assert!(vm.add_breakpoint(6));
+        assert!(vm.add_breakpoint(3));
+        assert!(!vm.add_breakpoint(9));
+        assert_eq!(vm.breakpoints(), vec![3, 6]);
+
+        assert_eq!(vm.resume(), Event::Breakpoint(6));
+        assert_eq!(vm.resume(), Event::Breakpoint(3));
+        assert_eq!(vm.resume(), Event::Breakpoint(6));
+
+        assert!(vm.remove_breakpoint(6));
+        assert!(!vm.remove_breakpoint(6));
+        assert_eq!(vm.resume(), Event::Breakpoint(3));
+
+        assert!(vm.remove_breakpoint(3));
+        assert_eq!(vm.resume(), Event::Loop(3));
+        vm.reset();
+        assert_eq!(vm.resume(), Event::Loop(1));
+    }
+
+    #[test]
+    fn test_watch_and_trace() {
+        let mut vm = Vm::new(&parse(RAW_INPUT).unwrap());
+        vm.set_watch_acc(true);
+        vm.set_tracing(true);
+
+        assert_eq!(
+            vm.resume(),
+            Event::AccChanged {
+                ip: 1,
+                before: 0,
+                after: 1
+            }
+        );
+        assert_eq!(
+            vm.resume(),
+            Event::AccChanged {
+                ip: 6,
+                before: 1,
+                after: 2
+            }
+        );
+
+        let trace = vm.trace();
+        assert_eq!(trace.len(), 4);
+        assert_eq!(trace[2].op, Op::jmp(4));
+        assert_eq!(trace[3].to_string(), "    6: acc +1     acc 1 -> 2");
+
+        vm.set_tracing(false);
+        assert!(vm.trace().is_empty());
+
+        // only the latest instructions are kept
+        let mut vm = Vm::new(&[Op::acc(1), Op::jmp(-1)]);
+        vm.set_tracing(true);
+        for _ in 0..TRACE_LIMIT * 3 {
+            vm.step();
+        }
+
+        let trace = vm.trace();
+        assert_eq!(trace.len(), TRACE_LIMIT);
+        assert_eq!(trace[0].acc_before, TRACE_LIMIT as isize);
+        assert_eq!(trace[TRACE_LIMIT - 1].acc_after, TRACE_LIMIT as isize * 3 / 2);
+    }
+}
@@ -1,7 +1,8 @@
 use std::collections::HashSet;
 
-use failure::{format_err, Error};
-use utils::{result, split_by_lines, RetTypes};
+use utils::{parse_token, split_by_lines, ParseResult, ProblemResult};
+
+use crate::problem::Problem;
 
 const WINDOW_SIZE: usize = 25;
 
@@ -25,7 +26,7 @@ fn find_pair_sum(input: &[usize], expected: usize) -> Option<(usize, usize)> {
 }
 
 fn first_star(input: &[usize], window_size: usize) -> usize {
-    for idx in 0..input.len() {
+    for idx in 0..input.len().saturating_sub(window_size) {
         match find_pair_sum(&input[idx..idx + window_size], input[idx + window_size]) {
             Some(_) => {}
             None => return input[idx + window_size],
@@ -48,24 +49,34 @@ fn second_star(input: &[usize], n: usize) -> usize {
     0
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input: Vec<usize> = split_by_lines(input_raw, &|e: &str| {
-        e.parse::<usize>()
-            .map_err(|_| format_err!("Failed to parse input"))
-    })?;
+pub struct Day9;
+
+impl Problem for Day9 {
+    const DAY: usize = 9;
+    const TITLE: &'static str = "Encoding Error";
+    const INPUT: &'static str = include_str!("./input");
+
+    type Input = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input_raw: &str) -> ParseResult<Self::Input> {
+        split_by_lines(input_raw, &|e: &str| parse_token(e, "number"))
+    }
 
-    let fst = first_star(&input, WINDOW_SIZE);
+    fn part1(input: &Self::Input) -> ProblemResult<Self::Answer1> {
+        Ok(first_star(input, WINDOW_SIZE))
+    }
 
-    Ok(RetTypes::Usize(result(
-        Ok(fst),
-        Ok(second_star(&input, fst)),
-    )))
+    fn part2(input: &Self::Input) -> ProblemResult<Self::Answer2> {
+        Ok(second_star(input, first_star(input, WINDOW_SIZE)))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{first_star, second_star};
+
     #[test]
     fn test_first() {
         let res = first_star(
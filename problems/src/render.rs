@@ -0,0 +1,238 @@
+use std::{
+    collections::HashMap, convert::TryFrom, fs::File, io::BufWriter, path::Path, str::FromStr,
+};
+
+use failure::{format_err, Error};
+use image::{
+    gif::{GifEncoder, Repeat},
+    Delay, Frame, Rgb, RgbImage, Rgba,
+};
+use utils::Grid;
+
+pub const DEFAULT_CELL_SIZE: u32 = 4;
+// delay between frames of an animated gif
+pub const DEFAULT_DELAY_MS: u32 = 200;
+// gif stores the width and the height of a frame in 16 bits
+pub const MAX_FRAME_SIDE: u32 = u16::MAX as u32;
+
+// colors of the characters cells are displayed as, every character not in the palette is black
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    colors: HashMap<char, Rgb<u8>>,
+}
+
+impl Palette {
+    pub fn color(&self, c: char) -> Rgb<u8> {
+        self.colors.get(&c).copied().unwrap_or(Rgb([0, 0, 0]))
+    }
+
+    pub fn set(&mut self, c: char, color: Rgb<u8>) {
+        self.colors.insert(c, color);
+    }
+
+    // entries of `other` take precedence
+    pub fn merge(mut self, other: &Palette) -> Self {
+        self.colors.extend(other.colors.iter());
+        self
+    }
+}
+
+// colors of the cells of both seating and cubes
+impl Default for Palette {
+    fn default() -> Self {
+        let colors = [
+            ('#', Rgb([0xf0, 0xa0, 0x30])),
+            ('L', Rgb([0x40, 0x60, 0xa0])),
+            ('.', Rgb([0x20, 0x20, 0x20])),
+        ];
+
+        Palette {
+            colors: colors.iter().copied().collect(),
+        }
+    }
+}
+
+impl FromStr for Palette {
+    type Err = Error;
+
+    // comma separated `<char>=<rrggbb>` entries, e.g. `#=ff0000,.=000000`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut colors = HashMap::new();
+
+        for entry in s.split(',') {
+            let wrong = || format_err!("wrong palette entry '{}', expected <char>=<rrggbb>", entry);
+
+            let mut chars = entry.chars();
+            let (c, color) = match (chars.next(), chars.next()) {
+                (Some(c), Some('=')) => (c, chars.as_str()),
+                _ => return Err(wrong()),
+            };
+
+            let hex = color.strip_prefix('#').unwrap_or(color);
+            if hex.len() != 6 {
+                return Err(wrong());
+            }
+
+            let mut rgb = [0; 3];
+            for (idx, channel) in rgb.iter_mut().enumerate() {
+                *channel = u8::from_str_radix(hex.get(idx * 2..idx * 2 + 2).ok_or_else(wrong)?, 16)
+                    .map_err(|_| wrong())?;
+            }
+
+            colors.insert(c, Rgb(rgb));
+        }
+
+        Ok(Palette { colors })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderOptions {
+    pub palette: Palette,
+    // side of a single cell in pixels
+    pub cell_size: u32,
+    pub delay_ms: u32,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            palette: Palette::default(),
+            cell_size: DEFAULT_CELL_SIZE,
+            delay_ms: DEFAULT_DELAY_MS,
+        }
+    }
+}
+
+// draws every cell as a square of its color, fails on a frame with any side longer
+// than `MAX_FRAME_SIDE` pixels
+pub fn frame<T: Copy + Into<char>>(
+    grid: &Grid<T>,
+    opts: &RenderOptions,
+) -> Result<RgbImage, Error> {
+    let size = opts.cell_size;
+    let side = |cells: usize| {
+        u32::try_from(cells)
+            .ok()
+            .and_then(|cells| cells.checked_mul(size))
+            .filter(|side| *side <= MAX_FRAME_SIDE)
+            .ok_or_else(|| {
+                format_err!(
+                    "{}x{} cells of {} pixels don't fit into a frame of at most {} pixels per side",
+                    grid.width(),
+                    grid.height(),
+                    size,
+                    MAX_FRAME_SIDE
+                )
+            })
+    };
+
+    let (width, height) = (side(grid.width())?, side(grid.height())?);
+
+    Ok(RgbImage::from_fn(width, height, |x, y| {
+        let cell = grid[((y / size) as usize, (x / size) as usize)];
+        opts.palette.color(cell.into())
+    }))
+}
+
+// `path` ending with `.gif` gets an animated gif, any other one a sequence of png files
+// numbered after its stem, e.g. `seats_000.png`, `seats_001.png` and so on for `seats.png`
+pub fn save(frames: &[RgbImage], path: &str, opts: &RenderOptions) -> Result<(), Error> {
+    if frames.is_empty() {
+        return Err(format_err!("nothing to render"));
+    }
+
+    let path = Path::new(path);
+
+    if path.extension().is_some_and(|ext| ext == "gif") {
+        let file = File::create(path)
+            .map_err(|err| format_err!("can't create '{}': {}", path.display(), err))?;
+
+        let mut encoder = GifEncoder::new(BufWriter::new(file));
+        encoder.set_repeat(Repeat::Infinite)?;
+        encoder.encode_frames(frames.iter().map(|image| {
+            let rgba = image::ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+                let Rgb([r, g, b]) = *image.get_pixel(x, y);
+                Rgba([r, g, b, 0xff])
+            });
+            Frame::from_parts(rgba, 0, 0, Delay::from_numer_denom_ms(opts.delay_ms, 1))
+        }))?;
+
+        return Ok(());
+    }
+
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| format_err!("wrong output path '{}'", path.display()))?;
+    let digits = (frames.len() - 1).to_string().len().max(3);
+
+    for (idx, image) in frames.iter().enumerate() {
+        let frame_path =
+            path.with_file_name(format!("{}_{:0width$}.png", stem, idx, width = digits));
+        image
+            .save(&frame_path)
+            .map_err(|err| format_err!("can't save '{}': {}", frame_path.display(), err))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{frame, Palette, RenderOptions, MAX_FRAME_SIDE};
+    use image::Rgb;
+    use utils::Grid;
+
+    #[test]
+    fn test_palette() {
+        let palette: Palette = "#=ff8000,.=#000010".parse().unwrap();
+        assert_eq!(palette.color('#'), Rgb([0xff, 0x80, 0x00]));
+        assert_eq!(palette.color('.'), Rgb([0x00, 0x00, 0x10]));
+        assert_eq!(palette.color('x'), Rgb([0, 0, 0]));
+
+        let merged = Palette::default().merge(&palette);
+        assert_eq!(merged.color('#'), Rgb([0xff, 0x80, 0x00]));
+        assert_eq!(merged.color('L'), Palette::default().color('L'));
+
+        assert!("#".parse::<Palette>().is_err());
+        assert!("#=ff80".parse::<Palette>().is_err());
+        assert!("#=gg8000".parse::<Palette>().is_err());
+        assert!("#:ff8000".parse::<Palette>().is_err());
+    }
+
+    #[test]
+    fn test_frame() {
+        let grid = Grid::parse_with("#.\n..", Some).unwrap();
+        let opts = RenderOptions {
+            palette: "#=ffffff,.=000000".parse().unwrap(),
+            cell_size: 3,
+            ..RenderOptions::default()
+        };
+
+        let image = frame(&grid, &opts).unwrap();
+        assert_eq!(image.dimensions(), (6, 6));
+        assert_eq!(*image.get_pixel(2, 2), Rgb([0xff, 0xff, 0xff]));
+        assert_eq!(*image.get_pixel(3, 2), Rgb([0, 0, 0]));
+        assert_eq!(*image.get_pixel(0, 5), Rgb([0, 0, 0]));
+
+        // neither an overflowing nor a too long side gets allocated
+        for cell_size in [u32::MAX, MAX_FRAME_SIDE / 2 + 1] {
+            let opts = RenderOptions {
+                cell_size,
+                ..RenderOptions::default()
+            };
+            assert!(frame(&grid, &opts).is_err());
+        }
+
+        let grid = Grid::parse_with(&".".repeat(MAX_FRAME_SIDE as usize), Some).unwrap();
+        let opts = RenderOptions {
+            cell_size: 1,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            frame(&grid, &opts).unwrap().dimensions(),
+            (MAX_FRAME_SIDE, 1)
+        );
+    }
+}
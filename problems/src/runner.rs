@@ -0,0 +1,312 @@
+use std::{
+    any::Any,
+    error,
+    fmt::{self, Display, Formatter},
+    iter,
+    panic::{self, AssertUnwindSafe},
+    time::{Duration, Instant},
+};
+
+use colored::*;
+use crossbeam::{queue::SegQueue, thread};
+use failure::Error;
+use utils::{Answer, Cancelled, Part, ProblemResult};
+
+use crate::problem::Solver;
+
+// error of a task which has panicked, keeps the panic message
+#[derive(Debug)]
+pub struct Panicked(pub String);
+
+impl Display for Panicked {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl error::Error for Panicked {}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Ok,
+    Error,
+    Panic,
+    Timeout,
+}
+
+impl Outcome {
+    pub fn of_error(err: &Error) -> Self {
+        if err.downcast_ref::<Panicked>().is_some() {
+            Outcome::Panic
+        } else if err.downcast_ref::<Cancelled>().is_some() {
+            Outcome::Timeout
+        } else {
+            Outcome::Error
+        }
+    }
+
+    pub fn of<T>(result: Result<T, &Error>) -> Self {
+        result.map_or_else(Outcome::of_error, |_| Outcome::Ok)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::Error => "error",
+            Outcome::Panic => "panic",
+            Outcome::Timeout => "timeout",
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_owned()
+    }
+}
+
+// a panic of a single task must not take the whole worker pool down
+fn guard<T, F: FnOnce() -> Result<T, Error>>(f: F) -> Result<T, Error> {
+    panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|payload| Err(Panicked(panic_message(payload)).into()))
+}
+
+pub struct PartReport {
+    pub part: Part,
+    pub answer: ProblemResult<Answer>,
+    pub elapsed: Duration,
+}
+
+pub struct Report {
+    pub day: usize,
+    pub title: &'static str,
+    // id of the worker the day has been executed by
+    pub worker: usize,
+    // parse error, none of the parts have been run if it is set
+    pub error: Option<Error>,
+    pub parse_elapsed: Duration,
+    pub parts: Vec<PartReport>,
+    pub elapsed: Duration,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none() && self.parts.iter().all(|part| part.answer.is_ok())
+    }
+}
+
+// `timeout` is a deadline for parse and all the parts together
+pub fn exec(
+    solver: &dyn Solver,
+    input: &str,
+    parts: &[Part],
+    worker: usize,
+    timeout: Option<Duration>,
+) -> Report {
+    let now = Instant::now();
+    utils::set_deadline(timeout.map(|timeout| now + timeout));
+
+    let mut report = Report {
+        day: solver.day(),
+        title: solver.title(),
+        worker,
+        error: None,
+        parse_elapsed: Duration::default(),
+        parts: vec![],
+        elapsed: Duration::default(),
+    };
+
+    let parsed = guard(|| Ok(solver.parse(input)?));
+    report.parse_elapsed = now.elapsed();
+
+    match parsed {
+        Err(err) => report.error = Some(err),
+        Ok(parsed) => {
+            for part in parts {
+                let part_now = Instant::now();
+                let answer = guard(|| solver.solve(parsed.as_ref(), *part));
+
+                report.parts.push(PartReport {
+                    part: *part,
+                    answer,
+                    elapsed: part_now.elapsed(),
+                });
+            }
+        }
+    }
+
+    utils::set_deadline(None);
+    report.elapsed = now.elapsed();
+
+    report
+}
+
+// parts of the solver to run, all of them unless a single one is asked for
+pub fn selected_parts(solver: &dyn Solver, part: Option<Part>) -> Vec<Part> {
+    solver
+        .parts()
+        .iter()
+        .copied()
+        .filter(|p| part.is_none() || part == Some(*p))
+        .collect()
+}
+
+// runs solvers on a pool of one worker per physical core, each of them on its own input unless
+// `input` is given, `on_report` is called by a worker as soon as a day is done,
+// the reports are returned sorted by day
+pub fn run_pool<F>(
+    solvers: &[&dyn Solver],
+    input: Option<&str>,
+    part: Option<Part>,
+    timeout: Option<Duration>,
+    on_report: F,
+) -> Vec<Report>
+where
+    F: Fn(&Report) + Sync,
+{
+    let q = SegQueue::new();
+    for solver in solvers {
+        q.push(*solver);
+    }
+
+    let reports = SegQueue::new();
+
+    thread::scope(|s| {
+        for idx in 0..num_cpus::get_physical() {
+            let (q, reports, on_report) = (&q, &reports, &on_report);
+            s.spawn(move |_| {
+                while let Ok(solver) = q.pop() {
+                    let report = exec(
+                        solver,
+                        input.unwrap_or(solver.input()),
+                        &selected_parts(solver, part),
+                        idx,
+                        timeout,
+                    );
+
+                    on_report(&report);
+                    reports.push(report);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    let mut reports: Vec<Report> = iter::from_fn(|| reports.pop().ok()).collect();
+    reports.sort_by_key(|report| report.day);
+
+    reports
+}
+
+// parse errors and parts of every outcome, e.g. "36 ok, 1 error, 1 panic"
+pub fn summary(reports: &[Report]) -> String {
+    let outcomes: Vec<Outcome> = reports
+        .iter()
+        .flat_map(|report| {
+            report.error.iter().map(Outcome::of_error).chain(
+                report
+                    .parts
+                    .iter()
+                    .map(|part| Outcome::of(part.answer.as_ref())),
+            )
+        })
+        .collect();
+
+    [
+        Outcome::Ok,
+        Outcome::Error,
+        Outcome::Panic,
+        Outcome::Timeout,
+    ]
+    .iter()
+    .map(|outcome| (outcome, outcomes.iter().filter(|o| *o == outcome).count()))
+    .filter(|(outcome, count)| **outcome == Outcome::Ok || *count > 0)
+    .map(|(outcome, count)| {
+        let text = format!("{} {}", count, outcome.label());
+        match outcome {
+            Outcome::Ok => text.green(),
+            _ => text.bold().red(),
+        }
+        .to_string()
+    })
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+fn outcome_label(outcome: Outcome) -> ColoredString {
+    outcome.label().bold().red()
+}
+
+pub fn print(report: &Report) {
+    let mut lines = vec![format!(
+        "{} {} ({}):",
+        "problem".bold(),
+        report.day.to_string().bold(),
+        report.title
+    )];
+
+    if let Some(err) = &report.error {
+        lines.push(format!(
+            "{}: {}",
+            outcome_label(Outcome::of_error(err)),
+            err
+        ));
+    }
+
+    for part in &report.parts {
+        let label = match part.part {
+            Part::First => "first star solution".blue(),
+            Part::Second => "second star solution".yellow(),
+        };
+
+        match &part.answer {
+            // rendered grids and such start on their own line
+            Ok(answer) if answer.is_multiline() => lines.push(format!("{}:\n{}", label, answer)),
+            Ok(answer) => lines.push(format!("{}: {}", label, answer)),
+            Err(err) => lines.push(format!(
+                "{}: {}: {}",
+                label,
+                outcome_label(Outcome::of_error(err)),
+                err
+            )),
+        }
+    }
+
+    // print everything at once, so output of different workers doesn't interleave
+    println!(
+        "{}\ntime elapsed for problem: {} millis\n",
+        lines.join("\n"),
+        report.elapsed.as_millis()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{guard, Outcome, Panicked};
+    use failure::format_err;
+    use utils::Cancelled;
+
+    #[test]
+    fn test_guard() {
+        assert_eq!(guard(|| Ok(42)).unwrap(), 42);
+
+        let err = guard::<(), _>(|| panic!("boom {}", 42)).unwrap_err();
+        assert_eq!(err.to_string(), "boom 42");
+        assert_eq!(Outcome::of_error(&err), Outcome::Panic);
+    }
+
+    #[test]
+    fn test_outcome() {
+        assert_eq!(Outcome::of(Ok::<_, &failure::Error>(1)), Outcome::Ok);
+        assert_eq!(Outcome::of_error(&format_err!("bad")), Outcome::Error);
+        assert_eq!(Outcome::of_error(&Cancelled.into()), Outcome::Timeout);
+        assert_eq!(
+            Outcome::of_error(&Panicked("boom".to_owned()).into()).label(),
+            "panic"
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use colored::*;
+use failure::{format_err, Error};
+use utils::{Answer, Part};
+
+use problems::runner::{Outcome, Report};
+
+// known answers for the embedded inputs
+pub(crate) const ANSWERS: &str = include_str!("../answers");
+
+pub(crate) type Answers = HashMap<(usize, Part), String>;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Status {
+    Pass,
+    Fail,
+    Error,
+    Panic,
+    Timeout,
+    // there is no known answer for this part yet
+    New,
+}
+
+impl Status {
+    fn is_ok(self) -> bool {
+        self == Status::Pass || self == Status::New
+    }
+
+    fn label(self) -> ColoredString {
+        match self {
+            Status::Pass => "pass".green(),
+            Status::Fail => "FAIL".bold().red(),
+            Status::Error => "ERROR".bold().red(),
+            Status::Panic => "PANIC".bold().red(),
+            Status::Timeout => "TIMEOUT".bold().red(),
+            Status::New => "new".yellow(),
+        }
+    }
+}
+
+// every non-empty line which isn't a comment is `<day> <part> <answer>`
+pub(crate) fn parse_answers(input_raw: &str) -> Result<Answers, Error> {
+    let mut answers = HashMap::new();
+
+    for (line_idx, line) in input_raw.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut splitter = line.splitn(3, char::is_whitespace);
+
+        let (day, part, answer) = match (splitter.next(), splitter.next(), splitter.next()) {
+            (Some(day), Some(part), Some(answer)) => (day, part, answer.trim()),
+            _ => {
+                return Err(format_err!(
+                    "line {}: expected '<day> <part> <answer>'",
+                    line_idx + 1
+                ))
+            }
+        };
+
+        let day = day
+            .parse::<usize>()
+            .map_err(|_| format_err!("line {}: wrong day '{}'", line_idx + 1, day))?;
+        let part = part
+            .parse::<Part>()
+            .map_err(|err| format_err!("line {}: {}", line_idx + 1, err))?;
+
+        if answers.insert((day, part), answer.to_owned()).is_some() {
+            return Err(format_err!(
+                "line {}: duplicate answer for day {} part {}",
+                line_idx + 1,
+                day,
+                part
+            ));
+        }
+    }
+
+    Ok(answers)
+}
+
+// answers file is line based, so newlines of multi-line answers are escaped there
+fn escape(answer: &Answer) -> String {
+    answer.to_string().replace('\n', "\\n")
+}
+
+fn error_status(err: &Error) -> Status {
+    match Outcome::of_error(err) {
+        Outcome::Panic => Status::Panic,
+        Outcome::Timeout => Status::Timeout,
+        _ => Status::Error,
+    }
+}
+
+fn status(expected: Option<&String>, actual: Result<&Answer, &Error>) -> Status {
+    match (expected, actual) {
+        (_, Err(err)) => error_status(err),
+        (None, Ok(_)) => Status::New,
+        (Some(expected), Ok(actual)) if *expected == escape(actual) => Status::Pass,
+        (Some(_), Ok(_)) => Status::Fail,
+    }
+}
+
+// prints a pass/fail table, returns false on any mismatch or error
+pub(crate) fn verify(answers: &Answers, reports: &[Report]) -> bool {
+    // day, part, expected, actual, status
+    let mut rows: Vec<(usize, String, String, String, Status)> = vec![];
+
+    for report in reports {
+        // none of the parts have been run, so the whole day is reported as a single row
+        if let Some(err) = &report.error {
+            rows.push((
+                report.day,
+                "-".to_owned(),
+                "-".to_owned(),
+                err.to_string(),
+                error_status(err),
+            ));
+        }
+
+        for part in &report.parts {
+            let expected = answers.get(&(report.day, part.part));
+
+            rows.push((
+                report.day,
+                part.part.to_string(),
+                expected.cloned().unwrap_or_else(|| "-".to_owned()),
+                match &part.answer {
+                    Ok(answer) => escape(answer),
+                    Err(err) => err.to_string(),
+                },
+                status(expected, part.answer.as_ref()),
+            ));
+        }
+    }
+
+    let expected_width = rows
+        .iter()
+        .map(|row| row.2.len())
+        .chain(Some("expected".len()))
+        .max()
+        .unwrap();
+    let actual_width = rows
+        .iter()
+        .map(|row| row.3.len())
+        .chain(Some("actual".len()))
+        .max()
+        .unwrap();
+
+    println!(
+        "{:>3}  {:>4}  {:<ew$}  {:<aw$}  {}",
+        "day".bold(),
+        "part".bold(),
+        "expected".bold(),
+        "actual".bold(),
+        "status".bold(),
+        ew = expected_width,
+        aw = actual_width
+    );
+
+    for (day, part, expected, actual, status) in &rows {
+        println!(
+            "{:>3}  {:>4}  {:<ew$}  {:<aw$}  {}",
+            day,
+            part,
+            expected,
+            actual,
+            status.label(),
+            ew = expected_width,
+            aw = actual_width
+        );
+    }
+
+    let failed = rows.iter().filter(|row| !row.4.is_ok()).count();
+
+    println!();
+    if failed == 0 {
+        println!("{} {} parts verified", "OK:".bold().green(), rows.len());
+    } else {
+        println!(
+            "{} {} of {} parts failed",
+            "FAILED:".bold().red(),
+            failed,
+            rows.len()
+        );
+    }
+
+    failed == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_answers, status, Status, ANSWERS};
+    use failure::{format_err, Error};
+    use problems::runner::Panicked;
+    use utils::Cancelled;
+    use utils::{Answer, Part};
+
+    #[test]
+    fn test_parse() {
+        let answers = parse_answers("# comment\n\n1 1 42\n1 2 some text answer\n").unwrap();
+
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[&(1, Part::First)], "42");
+        assert_eq!(answers[&(1, Part::Second)], "some text answer");
+
+        assert!(parse_answers("1 3 42").is_err());
+        assert!(parse_answers("x 1 42").is_err());
+        assert!(parse_answers("1 1").is_err());
+        assert!(parse_answers("1 1 42\n1 1 43").is_err());
+    }
+
+    #[test]
+    fn test_known_answers() {
+        let answers = parse_answers(ANSWERS).unwrap();
+
+        // every registered day must have both answers known
+        for solver in problems::PROBLEMS {
+            for part in solver.parts() {
+                assert!(answers.contains_key(&(solver.day(), *part)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_status() {
+        let expected = "42".to_owned();
+        let (right, wrong) = (Answer::from(42), Answer::from(43));
+        let err = format_err!("oops");
+
+        assert_eq!(status(Some(&expected), Ok(&right)), Status::Pass);
+        assert_eq!(status(Some(&expected), Ok(&wrong)), Status::Fail);
+        assert_eq!(status(Some(&expected), Err(&err)), Status::Error);
+        assert_eq!(status(None, Ok(&wrong)), Status::New);
+        assert_eq!(status(None, Err(&err)), Status::Error);
+
+        let panicked = Error::from(Panicked("oops".to_owned()));
+        assert_eq!(status(Some(&expected), Err(&panicked)), Status::Panic);
+        assert_eq!(status(None, Err(&Error::from(Cancelled))), Status::Timeout);
+
+        let grid = Answer::from("#.\n.#");
+        assert_eq!(status(Some(&"#.\\n.#".to_owned()), Ok(&grid)), Status::Pass);
+    }
+}
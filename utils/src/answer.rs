@@ -0,0 +1,128 @@
+use std::fmt::{self, Display, Formatter};
+
+use num::{BigInt, ToPrimitive};
+use serde::{Serialize, Serializer};
+
+// answer of a single part, every integer type, string and big integer converts into it,
+// so days are free to return whatever suits them best
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Answer {
+    // integers of any width which fit into i128
+    Int(i128),
+    // everything else, never holds a value which fits into `Int`
+    BigInt(BigInt),
+    Str(String),
+    // multi-line answers, like rendered grids
+    Text(String),
+}
+
+impl Answer {
+    pub fn is_multiline(&self) -> bool {
+        matches!(self, Answer::Text(_))
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{}", n),
+            Answer::BigInt(n) => write!(f, "{}", n),
+            Answer::Str(s) | Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+// ints are numbers, everything else including big ints is a string, so nothing loses
+// precision when read by a tool which keeps numbers as doubles
+impl Serialize for Answer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Answer::Int(n) => serializer.serialize_i128(*n),
+            Answer::BigInt(n) => serializer.serialize_str(&n.to_string()),
+            Answer::Str(s) | Answer::Text(s) => serializer.serialize_str(s),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Int(n as i128)
+                }
+            }
+        )*
+    };
+}
+
+answer_from_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, i128);
+
+impl From<u128> for Answer {
+    fn from(n: u128) -> Self {
+        match n.to_i128() {
+            Some(n) => Answer::Int(n),
+            None => Answer::BigInt(BigInt::from(n)),
+        }
+    }
+}
+
+impl From<BigInt> for Answer {
+    fn from(n: BigInt) -> Self {
+        match n.to_i128() {
+            Some(n) => Answer::Int(n),
+            None => Answer::BigInt(n),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        if s.contains('\n') {
+            Answer::Text(s)
+        } else {
+            Answer::Str(s)
+        }
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::from(s.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Answer;
+    use num::BigInt;
+
+    #[test]
+    fn test_from() {
+        assert_eq!(Answer::from(42_usize), Answer::Int(42));
+        assert_eq!(Answer::from(-42_isize), Answer::Int(-42));
+        assert_eq!(Answer::from(42_u8), Answer::from(42_i64));
+        assert_eq!(Answer::from(BigInt::from(42)), Answer::Int(42));
+        assert_eq!(Answer::from(u128::MAX).to_string(), u128::MAX.to_string());
+        assert!(matches!(Answer::from(u128::MAX), Answer::BigInt(_)));
+
+        assert_eq!(Answer::from("abc"), Answer::Str("abc".to_owned()));
+        assert!(Answer::from("#.\n.#").is_multiline());
+        assert_eq!(Answer::from("#.\n.#").to_string(), "#.\n.#");
+    }
+
+    #[test]
+    fn test_serialize() {
+        let big = BigInt::from(u128::MAX) * BigInt::from(10);
+
+        assert_eq!(serde_json::to_string(&Answer::from(42)).unwrap(), "42");
+        assert_eq!(
+            serde_json::to_string(&Answer::from(big.clone())).unwrap(),
+            format!("\"{}\"", big)
+        );
+        assert_eq!(
+            serde_json::to_string(&Answer::from("a\nb")).unwrap(),
+            "\"a\\nb\""
+        );
+    }
+}
@@ -0,0 +1,420 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display, Formatter},
+    hash::Hash,
+    mem,
+    str::FromStr,
+};
+
+use failure::{format_err, Error};
+
+use crate::{Grid, ProblemResult};
+
+// life-like birth/survival rules, e.g. "B3/S23" for the Conway's game of life:
+// a dead cell with 3 alive neighbours is born, an alive one with 2 or 3 of them survives
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    birth: Vec<usize>,
+    survival: Vec<usize>,
+}
+
+impl Rules {
+    pub fn new(birth: &[usize], survival: &[usize]) -> Self {
+        let sorted = |counts: &[usize]| {
+            let mut counts = counts.to_vec();
+            counts.sort_unstable();
+            counts.dedup();
+            counts
+        };
+
+        Rules {
+            birth: sorted(birth),
+            survival: sorted(survival),
+        }
+    }
+
+    // state of a cell in the next generation
+    pub fn next(&self, alive: bool, neighbours: usize) -> bool {
+        if alive {
+            self.survival.contains(&neighbours)
+        } else {
+            self.birth.contains(&neighbours)
+        }
+    }
+}
+
+impl FromStr for Rules {
+    type Err = Error;
+
+    // both halves are required and may go in any order, every digit is a count of neighbours
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wrong = || format_err!("wrong rules '{}', expected something like B3/S23", s);
+
+        let (first, second) = s.split_once('/').ok_or_else(wrong)?;
+        let (mut birth, mut survival) = (None, None);
+
+        for half in &[first, second] {
+            let mut chars = half.trim().chars();
+            let slot = match chars.next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') => &mut birth,
+                Some('S') => &mut survival,
+                _ => return Err(wrong()),
+            };
+
+            let counts = chars
+                .map(|c| c.to_digit(10).map(|d| d as usize))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(wrong)?;
+
+            if slot.replace(counts).is_some() {
+                return Err(wrong());
+            }
+        }
+
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(Rules::new(&birth, &survival)),
+            _ => Err(wrong()),
+        }
+    }
+}
+
+impl Display for Rules {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let digits = |counts: &[usize]| counts.iter().map(|n| n.to_string()).collect::<String>();
+        write!(f, "B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
+
+// shared by every topology, only a single step has to be implemented
+pub trait Automaton: Sized {
+    // advances by one generation, returns false if nothing has changed
+    fn step(&mut self) -> bool;
+
+    // the same as `step`, but a long one may give up midway with an error, e.g. once
+    // the deadline has passed, the automaton is left as it was then
+    fn try_step(&mut self) -> ProblemResult<bool> {
+        Ok(self.step())
+    }
+
+    // number of generations run so far
+    fn generation(&self) -> usize;
+
+    // number of alive cells
+    fn population(&self) -> usize;
+
+    // runs `generations` more generations, `on_generation` is called after each of them
+    // and stops the run with an error as soon as it returns one
+    fn run<F>(&mut self, generations: usize, mut on_generation: F) -> ProblemResult<()>
+    where
+        F: FnMut(&Self) -> ProblemResult<()>,
+    {
+        for _ in 0..generations {
+            self.try_step()?;
+            on_generation(self)?;
+        }
+
+        Ok(())
+    }
+
+    // runs until a generation is the same as the one before it, returns the number of generations
+    // it took, the last one which hasn't changed anything included
+    fn run_until_stable<F>(&mut self, mut on_generation: F) -> ProblemResult<usize>
+    where
+        F: FnMut(&Self) -> ProblemResult<()>,
+    {
+        let start = self.generation();
+
+        loop {
+            let changed = self.try_step()?;
+            on_generation(self)?;
+
+            if !changed {
+                return Ok(self.generation() - start);
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CellState {
+    Dead,
+    Alive,
+    // not a part of the world, never alive, like a floor between seats
+    Void,
+}
+
+// neighbours of the cell at (row, column), the board is given to allow looking through void,
+// it is called once per cell on the initial board, so it may only depend on where void is
+pub type DenseNeighbourhood<'a> =
+    dyn Fn(&Grid<CellState>, usize, usize) -> Vec<(usize, usize)> + 'a;
+
+// automaton on a finite board
+pub struct DenseAutomaton {
+    board: Grid<CellState>,
+    // the generation before the current one, reused to avoid allocations
+    prev: Grid<CellState>,
+    rules: Rules,
+    // flat indices of the neighbours of the cell at flat index `idx` are
+    // `neighbours[offsets[idx]..offsets[idx + 1]]`, void cells have none
+    offsets: Vec<usize>,
+    neighbours: Vec<usize>,
+    generation: usize,
+}
+
+impl DenseAutomaton {
+    pub fn new(board: Grid<CellState>, rules: Rules, neighbourhood: &DenseNeighbourhood) -> Self {
+        let mut offsets = Vec::with_capacity(board.cells().len() + 1);
+        let mut neighbours = vec![];
+
+        offsets.push(0);
+        for (row, col) in board.positions() {
+            if board[(row, col)] != CellState::Void {
+                neighbours.extend(
+                    neighbourhood(&board, row, col)
+                        .into_iter()
+                        .filter_map(|(row, col)| board.index_of(row, col)),
+                );
+            }
+            offsets.push(neighbours.len());
+        }
+
+        DenseAutomaton {
+            prev: board.clone(),
+            board,
+            rules,
+            offsets,
+            neighbours,
+            generation: 0,
+        }
+    }
+
+    pub fn board(&self) -> &Grid<CellState> {
+        &self.board
+    }
+}
+
+impl Automaton for DenseAutomaton {
+    fn step(&mut self) -> bool {
+        mem::swap(&mut self.prev, &mut self.board);
+
+        let prev = self.prev.cells();
+        let mut changed = false;
+
+        for (idx, cell) in self.board.cells_mut().iter_mut().enumerate() {
+            let state = prev[idx];
+
+            let next = if state == CellState::Void {
+                state
+            } else {
+                let neighbours = self.neighbours[self.offsets[idx]..self.offsets[idx + 1]]
+                    .iter()
+                    .filter(|neighbour| prev[**neighbour] == CellState::Alive)
+                    .count();
+
+                if self.rules.next(state == CellState::Alive, neighbours) {
+                    CellState::Alive
+                } else {
+                    CellState::Dead
+                }
+            };
+
+            changed |= next != state;
+            *cell = next;
+        }
+
+        self.generation += 1;
+
+        changed
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn population(&self) -> usize {
+        self.board
+            .iter()
+            .filter(|state| **state == CellState::Alive)
+            .count()
+    }
+}
+
+// automaton in an unbounded space of any number of dimensions, only alive cells are stored,
+// so nothing is ever born out of nothing, even with B0
+pub struct SparseAutomaton<'a, P> {
+    alive: HashSet<P>,
+    rules: Rules,
+    neighbourhood: &'a dyn Fn(&P) -> Vec<P>,
+    generation: usize,
+}
+
+impl<'a, P: Copy + Eq + Hash> SparseAutomaton<'a, P> {
+    pub fn new<I>(alive: I, rules: Rules, neighbourhood: &'a dyn Fn(&P) -> Vec<P>) -> Self
+    where
+        I: IntoIterator<Item = P>,
+    {
+        SparseAutomaton {
+            alive: alive.into_iter().collect(),
+            rules,
+            neighbourhood,
+            generation: 0,
+        }
+    }
+
+    pub fn alive(&self) -> &HashSet<P> {
+        &self.alive
+    }
+}
+
+impl<'a, P: Copy + Eq + Hash> Automaton for SparseAutomaton<'a, P> {
+    fn step(&mut self) -> bool {
+        // alive neighbours of every cell next to an alive one
+        let mut counts: HashMap<P, usize> = HashMap::new();
+
+        for cell in &self.alive {
+            for neighbour in (self.neighbourhood)(cell) {
+                *counts.entry(neighbour).or_default() += 1;
+            }
+        }
+
+        let mut next: HashSet<P> = counts
+            .iter()
+            .filter(|(cell, count)| self.rules.next(self.alive.contains(cell), **count))
+            .map(|(cell, _)| *cell)
+            .collect();
+
+        // lonely cells have no entry in `counts`
+        if self.rules.next(true, 0) {
+            next.extend(self.alive.iter().filter(|cell| !counts.contains_key(cell)));
+        }
+
+        let changed = next != self.alive;
+        self.alive = next;
+        self.generation += 1;
+
+        changed
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn population(&self) -> usize {
+        self.alive.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Automaton, CellState, DenseAutomaton, Rules, SparseAutomaton};
+    use crate::Grid;
+    use failure::format_err;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_rules() {
+        let rules: Rules = "B3/S23".parse().unwrap();
+        assert!(rules.next(false, 3));
+        assert!(!rules.next(false, 2));
+        assert!(rules.next(true, 2));
+        assert!(!rules.next(true, 4));
+
+        assert_eq!("s32/b3".parse::<Rules>().unwrap(), rules);
+        assert_eq!(rules.to_string(), "B3/S23");
+        assert_eq!("B0/S".parse::<Rules>().unwrap().to_string(), "B0/S");
+
+        assert!("B3".parse::<Rules>().is_err());
+        assert!("B3/B2".parse::<Rules>().is_err());
+        assert!("B3/S2x".parse::<Rules>().is_err());
+    }
+
+    fn blinker() -> Grid<CellState> {
+        Grid::parse_with(".....\n..#..\n..#..\n..#..\n.....", |c| match c {
+            '#' => Some(CellState::Alive),
+            '.' => Some(CellState::Dead),
+            _ => None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dense() {
+        let neighbourhood =
+            |board: &Grid<CellState>, row, col| board.neighbours8(row, col).collect();
+        let mut life = DenseAutomaton::new(blinker(), "B3/S23".parse().unwrap(), &neighbourhood);
+
+        let mut populations = vec![];
+        life.run(2, |life| {
+            populations.push(life.population());
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(populations, vec![3, 3]);
+        assert_eq!(life.generation(), 2);
+        assert_eq!(life.board(), &blinker());
+
+        // a blinker never settles down
+        let err = life.run_until_stable(|life| {
+            if life.generation() > 10 {
+                Err(format_err!("not stable"))
+            } else {
+                Ok(())
+            }
+        });
+        assert!(err.is_err());
+
+        // the neighbourhood is only looked up once for every non-void cell
+        let calls = Cell::new(0);
+        let counting = |board: &Grid<CellState>, row, col| {
+            calls.set(calls.get() + 1);
+            board.neighbours8(row, col).collect()
+        };
+        let mut board = blinker();
+        board[(0, 0)] = CellState::Void;
+        let mut life = DenseAutomaton::new(board, "B3/S23".parse().unwrap(), &counting);
+        life.run(4, |_| Ok(())).unwrap();
+        assert_eq!(calls.get(), 24);
+        assert_eq!(life.board()[(0, 0)], CellState::Void);
+
+        // everything dies out
+        let mut life = DenseAutomaton::new(blinker(), "B/S".parse().unwrap(), &neighbourhood);
+        assert_eq!(life.run_until_stable(|_| Ok(())).unwrap(), 2);
+        assert_eq!(life.population(), 0);
+    }
+
+    #[test]
+    fn test_sparse() {
+        let neighbourhood = |(x, y): &(isize, isize)| {
+            let mut res = vec![];
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    if (dx, dy) != (0, 0) {
+                        res.push((x + dx, y + dy));
+                    }
+                }
+            }
+            res
+        };
+
+        // a block is still
+        let block = vec![(0, 0), (0, 1), (1, 0), (1, 1)];
+        let mut life = SparseAutomaton::new(block, "B3/S23".parse().unwrap(), &neighbourhood);
+        assert_eq!(life.run_until_stable(|_| Ok(())).unwrap(), 1);
+        assert_eq!(life.population(), 4);
+
+        // a glider keeps its shape, moving one cell diagonally every 4 generations
+        let glider = vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mut life =
+            SparseAutomaton::new(glider.clone(), "B3/S23".parse().unwrap(), &neighbourhood);
+        life.run(4, |_| Ok(())).unwrap();
+        assert!(glider
+            .iter()
+            .all(|(x, y)| life.alive().contains(&(x + 1, y + 1))));
+
+        // lonely cells survive with S0
+        let mut life = SparseAutomaton::new(vec![(0, 0)], "B/S0".parse().unwrap(), &neighbourhood);
+        life.run(1, |_| Ok(())).unwrap();
+        assert_eq!(life.population(), 1);
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    cell::Cell,
+    error,
+    fmt::{self, Display, Formatter},
+    time::Instant,
+};
+
+thread_local! {
+    // deadline of the task which is running on the current thread
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+// returned by `check_deadline` once the deadline has passed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "deadline exceeded")
+    }
+}
+
+impl error::Error for Cancelled {}
+
+// set by the runner before every task, `None` means no deadline
+pub fn set_deadline(deadline: Option<Instant>) {
+    DEADLINE.with(|cell| cell.set(deadline));
+}
+
+// cancellation is cooperative, long running loops should call this every now and then
+// and bail out with the error as soon as it is returned
+pub fn check_deadline() -> Result<(), Cancelled> {
+    match DEADLINE.with(Cell::get) {
+        Some(deadline) if Instant::now() >= deadline => Err(Cancelled),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_deadline, set_deadline, Cancelled};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_deadline() {
+        assert_eq!(check_deadline(), Ok(()));
+
+        set_deadline(Some(Instant::now() + Duration::from_secs(60)));
+        assert_eq!(check_deadline(), Ok(()));
+
+        set_deadline(Some(Instant::now()));
+        assert_eq!(check_deadline(), Err(Cancelled));
+
+        // deadlines are per thread
+        assert_eq!(std::thread::spawn(check_deadline).join().unwrap(), Ok(()));
+
+        set_deadline(None);
+        assert_eq!(check_deadline(), Ok(()));
+    }
+}
@@ -0,0 +1,347 @@
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+    iter,
+    ops::{Index, IndexMut},
+};
+
+use crate::{ParseError, ParseResult};
+
+// (row, column) offsets of the neighbours sharing a side with a cell
+pub const DIRS_4: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+// (row, column) offsets of all the neighbours around a cell, clockwise from the north
+pub const DIRS_8: [(isize, isize); 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
+// rectangular board of cells stored row by row, positions are (row, column) pairs
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), width * height);
+
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    pub fn filled(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid::new(width, height, vec![value; width * height])
+    }
+
+    // parses a character map, every non-empty line is a row and every character a cell,
+    // `f` returns none for characters which don't stand for any cell
+    pub fn parse_with<F>(input_raw: &str, f: F) -> ParseResult<Self>
+    where
+        F: Fn(char) -> Option<T>,
+    {
+        let mut cells = vec![];
+        let mut width = None;
+        let mut height = 0;
+
+        for line in input_raw.lines().filter(|line| !line.is_empty()) {
+            for (idx, c) in line.char_indices() {
+                cells.push(f(c).ok_or_else(|| {
+                    ParseError::at(&line[idx..idx + c.len_utf8()], "unknown cell")
+                })?);
+            }
+
+            let line_width = line.chars().count();
+            if *width.get_or_insert(line_width) != line_width {
+                return Err(ParseError::at(line, "row width differs from the first one"));
+            }
+
+            height += 1;
+        }
+
+        match width {
+            Some(width) if width > 0 => Ok(Grid::new(width, height, cells)),
+            _ => Err(ParseError::new("the map is empty")),
+        }
+    }
+
+    // same as `parse_with`, cells are converted from characters with `TryFrom<char>`
+    pub fn parse(input_raw: &str) -> ParseResult<Self>
+    where
+        T: TryFrom<char>,
+    {
+        Grid::parse_with(input_raw, |c| T::try_from(c).ok())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // index of the cell in `cells`, positions of the cells go row by row
+    pub fn index_of(&self, row: usize, col: usize) -> Option<usize> {
+        if row < self.height && col < self.width {
+            Some(row * self.width + col)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        self.index_of(row, col).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        self.index_of(row, col).map(move |idx| &mut self.cells[idx])
+    }
+
+    // the board repeats itself in every direction
+    pub fn get_wrapping(&self, row: isize, col: isize) -> &T {
+        let row = row.rem_euclid(self.height as isize) as usize;
+        let col = col.rem_euclid(self.width as isize) as usize;
+
+        &self.cells[row * self.width + col]
+    }
+
+    // position one step away in the given direction, none if it is off the board
+    pub fn step(
+        &self,
+        (row, col): (usize, usize),
+        (d_row, d_col): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let row = row.checked_add_signed(d_row)?;
+        let col = col.checked_add_signed(d_col)?;
+
+        self.index_of(row, col).map(|_| (row, col))
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.width);
+        self.cells[col..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |col| self.column(col))
+    }
+
+    // positions of the neighbours in the given directions, off the board ones are skipped
+    pub fn neighbours<'a>(
+        &'a self,
+        row: usize,
+        col: usize,
+        dirs: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        dirs.iter()
+            .filter_map(move |dir| self.step((row, col), *dir))
+    }
+
+    pub fn neighbours4(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbours(row, col, &DIRS_4)
+    }
+
+    pub fn neighbours8(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbours(row, col, &DIRS_8)
+    }
+
+    // positions from the cell in the given direction up to the edge, the cell itself excluded
+    pub fn ray(
+        &self,
+        row: usize,
+        col: usize,
+        dir: (isize, isize),
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        iter::successors(self.step((row, col), dir), move |pos| self.step(*pos, dir))
+    }
+
+    // the first cell seen in each of 8 directions, looking through cells `transparent` holds for
+    pub fn ray_neighbours<'a, F>(
+        &'a self,
+        row: usize,
+        col: usize,
+        transparent: F,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a
+    where
+        F: Fn(&T) -> bool + 'a,
+    {
+        DIRS_8.iter().filter_map(move |dir| {
+            self.ray(row, col, *dir)
+                .find(|(row, col)| !transparent(&self[(*row, *col)]))
+        })
+    }
+
+    pub fn map<U, F: Fn(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        self.get(row, col)
+            .unwrap_or_else(|| panic!("({}, {}) is off the board", row, col))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        self.get_mut(row, col)
+            .unwrap_or_else(|| panic!("({}, {}) is off the board", row, col))
+    }
+}
+
+// back to the character map, one line per row
+impl<T: Copy + Into<char>> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self
+            .rows()
+            .map(|row| row.iter().map(|cell| (*cell).into()).collect())
+            .collect();
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Grid;
+    use std::convert::TryFrom;
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    enum Cell {
+        Empty,
+        Wall,
+    }
+
+    impl TryFrom<char> for Cell {
+        type Error = ();
+
+        fn try_from(c: char) -> Result<Self, Self::Error> {
+            match c {
+                '.' => Ok(Cell::Empty),
+                '#' => Ok(Cell::Wall),
+                _ => Err(()),
+            }
+        }
+    }
+
+    impl From<Cell> for char {
+        fn from(cell: Cell) -> Self {
+            match cell {
+                Cell::Empty => '.',
+                Cell::Wall => '#',
+            }
+        }
+    }
+
+    const MAP: &str = "#..\n.#.\n..#\n#..";
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::<Cell>::parse(MAP).unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (3, 4));
+        assert_eq!(grid[(1, 1)], Cell::Wall);
+        assert_eq!(grid.to_string(), MAP);
+
+        let err = Grid::<Cell>::parse("#.\n.o").unwrap_err().locate("#.\n.o");
+        assert_eq!((err.line, err.column), (Some(2), Some(2)));
+        assert!(Grid::<Cell>::parse("#.\n.").is_err());
+        assert!(Grid::<Cell>::parse("\n").is_err());
+
+        let grid = Grid::parse_with("ab\ncd", |c| Some(c as u8)).unwrap();
+        assert_eq!(grid.map(|c| *c as char).to_string(), "ab\ncd");
+    }
+
+    #[test]
+    fn test_access() {
+        let grid = Grid::<Cell>::parse(MAP).unwrap();
+
+        assert_eq!(grid.get(3, 0), Some(&Cell::Wall));
+        assert_eq!(grid.get(4, 0), None);
+        assert_eq!(grid.get(0, 3), None);
+
+        assert_eq!(*grid.get_wrapping(4, 3), Cell::Wall);
+        assert_eq!(*grid.get_wrapping(-1, -3), Cell::Wall);
+        assert_eq!(*grid.get_wrapping(-2, 1), Cell::Empty);
+
+        let mut grid = grid;
+        grid.cells_mut()[5] = Cell::Wall;
+        assert_eq!(grid[(1, 2)], Cell::Wall);
+
+        assert_eq!(grid.row(2), &[Cell::Empty, Cell::Empty, Cell::Wall]);
+        assert_eq!(grid.rows().count(), 4);
+        assert_eq!(
+            grid.column(0).copied().collect::<Vec<_>>(),
+            vec![Cell::Wall, Cell::Empty, Cell::Empty, Cell::Wall]
+        );
+        assert_eq!(grid.columns().count(), 3);
+        assert_eq!(grid.positions().nth(4), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::<Cell>::parse(MAP).unwrap();
+
+        let mut around: Vec<_> = grid.neighbours4(0, 0).collect();
+        around.sort_unstable();
+        assert_eq!(around, vec![(0, 1), (1, 0)]);
+
+        assert_eq!(grid.neighbours8(0, 0).count(), 3);
+        assert_eq!(grid.neighbours8(1, 1).count(), 8);
+
+        assert_eq!(
+            grid.ray(0, 0, (1, 1)).collect::<Vec<_>>(),
+            vec![(1, 1), (2, 2)]
+        );
+
+        // only walls stop the look, from (3, 2) only the walls to the north and the west are seen
+        let mut seen: Vec<_> = grid
+            .ray_neighbours(3, 2, |cell| *cell == Cell::Empty)
+            .collect();
+        seen.sort_unstable();
+        assert_eq!(seen, vec![(2, 2), (3, 0)]);
+    }
+}
@@ -67,7 +67,7 @@ mod tests {
 
     #[test]
     fn test_2() {
-        let input = concat!("abc",);
+        let input = "abc";
 
         let mut res = vec![];
 
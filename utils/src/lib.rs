@@ -1,40 +1,59 @@
 use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Sub};
 use std::{
-    fmt::{self, Debug, Display, Formatter},
+    fmt::{self, Display, Formatter},
+    str::FromStr,
     string::ToString,
 };
 
-use colored::*;
-use failure::Error;
+use failure::{format_err, Error};
 use num::FromPrimitive;
 use num_traits::{sign::Signed, CheckedDiv, Num};
 
 pub type ProblemResult<T> = Result<T, Error>;
-pub type ParseResult<T> = Result<T, Error>;
+pub type ParseResult<T> = Result<T, ParseError>;
 
+mod answer;
+mod automaton;
+mod deadline;
+mod grid;
 mod groups_iter;
-mod ret_types;
+mod modular;
+mod parse_error;
 
+pub use answer::Answer;
+pub use automaton::{
+    Automaton, CellState, DenseAutomaton, DenseNeighbourhood, Rules, SparseAutomaton,
+};
+pub use deadline::{check_deadline, set_deadline, Cancelled};
+pub use grid::{Grid, DIRS_4, DIRS_8};
 pub use groups_iter::GroupByEmptyLine;
-pub use ret_types::RetTypes;
+pub use modular::{crt, ext_gcd, mod_inverse, mul_mod, CrtError};
+pub use parse_error::{parse_token, ParseError};
 
-pub struct Ret<T, K> {
-    answer_basic: ProblemResult<T>,
-    answer_adv: ProblemResult<K>,
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Part {
+    First,
+    Second,
 }
 
-pub type RetOne<T> = Ret<T, T>;
-
-impl<T: Debug, K: Debug> Display for Ret<T, K> {
+impl Display for Part {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}: {:?}\n{}: {:?}",
-            "first star solution".blue(),
-            self.answer_basic,
-            "second star solution".yellow(),
-            self.answer_adv
-        )
+        match self {
+            Part::First => write!(f, "1"),
+            Part::Second => write!(f, "2"),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::First),
+            "2" => Ok(Part::Second),
+            _ => Err(format_err!("wrong part '{}', expected 1 or 2", s)),
+        }
     }
 }
 
@@ -106,19 +125,8 @@ where
     (n[0], n[1])
 }
 
-pub fn result<T: Debug, K: Debug>(basic: ProblemResult<T>, adv: ProblemResult<K>) -> Ret<T, K> {
-    Ret {
-        answer_basic: basic,
-        answer_adv: adv,
-    }
-}
-
 pub fn split_by_lines<T>(input: &str, f: &dyn Fn(&str) -> ParseResult<T>) -> ParseResult<Vec<T>> {
-    let res: ParseResult<Vec<_>> = input
-        .lines()
-        .filter(|item| item != &"")
-        .map(|item| f(item))
-        .collect();
+    let res: ParseResult<Vec<_>> = input.lines().filter(|item| item != &"").map(f).collect();
 
     res
 }
@@ -132,11 +140,7 @@ pub fn split_by<T>(
     sep: &str,
     f: &dyn Fn(&str) -> ParseResult<T>,
 ) -> ParseResult<Vec<T>> {
-    let res: ParseResult<Vec<_>> = input
-        .split(sep)
-        .filter(|item| item != &"")
-        .map(|item| f(item))
-        .collect();
+    let res: ParseResult<Vec<_>> = input.split(sep).filter(|item| item != &"").map(f).collect();
 
     res
 }
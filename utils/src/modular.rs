@@ -0,0 +1,173 @@
+use std::{
+    error,
+    fmt::{self, Display, Formatter},
+};
+
+use num::{BigInt, ToPrimitive};
+
+// returned by `crt` when the congruences can't be combined
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CrtError {
+    // moduli must be positive
+    WrongModulus(i128),
+    // the congruences before `conflict` combine into `x ≡ residue (mod modulus)`,
+    // which contradicts `x ≡ conflict.0 (mod conflict.1)`
+    NoSolution {
+        residue: i128,
+        modulus: i128,
+        conflict: (i128, i128),
+    },
+    // the least common multiple of the moduli doesn't fit into 128 bits
+    Overflow,
+}
+
+impl Display for CrtError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CrtError::WrongModulus(modulus) => write!(f, "modulus {} is not positive", modulus),
+            CrtError::NoSolution {
+                residue,
+                modulus,
+                conflict,
+            } => write!(
+                f,
+                "no solution, x = {} (mod {}) contradicts x = {} (mod {})",
+                residue, modulus, conflict.0, conflict.1
+            ),
+            CrtError::Overflow => write!(f, "the combined modulus doesn't fit into 128 bits"),
+        }
+    }
+}
+
+impl error::Error for CrtError {}
+
+// gcd of `a` and `b` along with `x` and `y` such that `a * x + b * y = gcd`,
+// the gcd is never negative
+pub fn ext_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (1, 0);
+    let (mut y0, mut y1) = (0, 1);
+
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+
+    if r0 < 0 {
+        (-r0, -x0, -y0)
+    } else {
+        (r0, x0, y0)
+    }
+}
+
+// `a * b` modulo a positive `m`, big integers take over once the product overflows
+pub fn mul_mod(a: i128, b: i128, m: i128) -> i128 {
+    let (a, b) = (a.rem_euclid(m), b.rem_euclid(m));
+
+    match a.checked_mul(b) {
+        Some(product) => product % m,
+        // the remainder is less than `m`, so it fits
+        None => (BigInt::from(a) * BigInt::from(b) % BigInt::from(m))
+            .to_i128()
+            .unwrap(),
+    }
+}
+
+// `x` such that `a * x ≡ 1 (mod m)` for a positive `m`, none unless `a` and `m` are coprime
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    match ext_gcd(a.rem_euclid(m), m) {
+        (1, x, _) => Some(x.rem_euclid(m)),
+        _ => None,
+    }
+}
+
+// the single `x ≡ residue (mod modulus)` every one of the `x ≡ r (mod m)` congruences holds for,
+// moduli don't have to be coprime, the modulus is their least common multiple and the residue
+// is the least non-negative solution
+pub fn crt(congruences: &[(i128, i128)]) -> Result<(i128, i128), CrtError> {
+    let (mut residue, mut modulus) = (0, 1);
+
+    for &(r, m) in congruences {
+        if m <= 0 {
+            return Err(CrtError::WrongModulus(m));
+        }
+
+        let r = r.rem_euclid(m);
+        let (gcd, x, _) = ext_gcd(modulus, m);
+
+        // both are non-negative, so there is no overflow
+        let diff = r - residue;
+        if diff % gcd != 0 {
+            return Err(CrtError::NoSolution {
+                residue,
+                modulus,
+                conflict: (r, m),
+            });
+        }
+
+        // residue + modulus * k ≡ r (mod m) for k ≡ diff / gcd * x (mod m / gcd)
+        let step = m / gcd;
+        let k = mul_mod(diff / gcd, x, step);
+        let lcm = modulus.checked_mul(step).ok_or(CrtError::Overflow)?;
+
+        // k < step, so the sum stays below the lcm
+        residue += modulus * k;
+        modulus = lcm;
+    }
+
+    Ok((residue, modulus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crt, ext_gcd, mod_inverse, mul_mod, CrtError};
+
+    #[test]
+    fn test_gcd_and_inverse() {
+        assert_eq!(ext_gcd(240, 46), (2, -9, 47));
+        assert_eq!(ext_gcd(-4, 6), (2, 1, 1));
+        assert_eq!(ext_gcd(0, 0), (0, 1, 0));
+
+        assert_eq!(mod_inverse(3, 7), Some(5));
+        assert_eq!(mod_inverse(-3, 7), Some(2));
+        assert_eq!(mod_inverse(4, 6), None);
+
+        // -1 * -1 with a product way past 128 bits
+        assert_eq!(mul_mod(i128::MAX - 1, i128::MAX - 1, i128::MAX), 1);
+        assert_eq!(mul_mod(-3, 5, 7), 6);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[]), Ok((0, 1)));
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        assert_eq!(crt(&[(-1, 4), (5, 6)]), Ok((11, 12)));
+        assert_eq!(crt(&[(0, 17), (-2, 13), (-3, 19)]), Ok((3417, 4199)));
+
+        assert_eq!(
+            crt(&[(1, 4), (2, 6)]),
+            Err(CrtError::NoSolution {
+                residue: 1,
+                modulus: 4,
+                conflict: (2, 6)
+            })
+        );
+        assert_eq!(
+            crt(&[(1, 4), (2, 6)]).unwrap_err().to_string(),
+            "no solution, x = 1 (mod 4) contradicts x = 2 (mod 6)"
+        );
+        assert_eq!(crt(&[(1, 0)]), Err(CrtError::WrongModulus(0)));
+        assert_eq!(
+            crt(&[(1, i128::MAX), (0, i128::MAX - 1)]),
+            Err(CrtError::Overflow)
+        );
+
+        // moduli close to 64 bits, their product is way past 64 bits
+        let (a, b) = ((1 << 62) + 135, (1 << 62) + 147);
+        let (x, m) = crt(&[(5, a), (7, b)]).unwrap();
+        assert_eq!(m, a * b);
+        assert_eq!((x % a, x % b), (5, 7));
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    error,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+// error of a malformed input, parsers only need to point at the offending piece of the input,
+// day, line and column are filled in later by whoever has the whole input at hand
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: Option<usize>,
+    // both line and column start from 1
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub text: String,
+    pub reason: String,
+    // address of `text` if it has been taken from the input, see `locate`
+    addr: Option<usize>,
+}
+
+impl ParseError {
+    // error without a particular place in the input, like a missing section
+    pub fn new<S: Into<String>>(reason: S) -> Self {
+        ParseError {
+            day: None,
+            line: None,
+            column: None,
+            text: String::new(),
+            reason: reason.into(),
+            addr: None,
+        }
+    }
+
+    // `token` must be a slice of the input for `locate` to find it
+    pub fn at<S: Into<String>>(token: &str, reason: S) -> Self {
+        ParseError {
+            text: token.to_owned(),
+            addr: Some(token.as_ptr() as usize),
+            ..ParseError::new(reason)
+        }
+    }
+
+    // points right after `token`, for things which are missing at the end of it
+    pub fn after<S: Into<String>>(token: &str, reason: S) -> Self {
+        ParseError::at(&token[token.len()..], reason)
+    }
+
+    pub fn with_day(mut self, day: usize) -> Self {
+        self.day = Some(day);
+        self
+    }
+
+    // turns the address of the offending text into line and column, if it points into `input`
+    pub fn locate(mut self, input: &str) -> Self {
+        let (addr, start) = match self.addr {
+            Some(addr) => (addr, input.as_ptr() as usize),
+            None => return self,
+        };
+
+        if addr < start || addr > start + input.len() || !input.is_char_boundary(addr - start) {
+            return self;
+        }
+
+        let before = &input[..addr - start];
+        let line_start = before.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+
+        self.line = Some(before.matches('\n').count() + 1);
+        self.column = Some(before[line_start..].chars().count() + 1);
+
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut location = vec![];
+
+        if let Some(day) = self.day {
+            location.push(format!("day {}", day));
+        }
+        if let Some(line) = self.line {
+            location.push(format!("line {}", line));
+        }
+        if let Some(column) = self.column {
+            location.push(format!("column {}", column));
+        }
+
+        if !location.is_empty() {
+            write!(f, "{}: ", location.join(", "))?;
+        }
+
+        if self.text.is_empty() {
+            write!(f, "{}", self.reason)
+        } else {
+            write!(f, "{} '{}'", self.reason, self.text)
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+// parses a single value, `what` names it in the error message
+pub fn parse_token<T: FromStr>(token: &str, what: &str) -> Result<T, ParseError> {
+    let token = token.trim();
+
+    token
+        .parse::<T>()
+        .map_err(|_| ParseError::at(token, format!("wrong {}", what)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_token, ParseError};
+
+    #[test]
+    fn test_locate() {
+        let input = "12\n34\nx6\n";
+        let token = &input[6..7];
+
+        let err = ParseError::at(token, "wrong number").locate(input);
+        assert_eq!((err.line, err.column), (Some(3), Some(1)));
+        assert_eq!(err.to_string(), "line 3, column 1: wrong number 'x'");
+
+        let err = ParseError::after(&input[3..5], "missing value").locate(input);
+        assert_eq!((err.line, err.column), (Some(2), Some(3)));
+        assert_eq!(
+            err.with_day(7).to_string(),
+            "day 7, line 2, column 3: missing value"
+        );
+
+        // text which doesn't come from the input can't be located
+        let other = "x6".to_owned();
+        let err = ParseError::at(&other, "wrong number").locate(input);
+        assert_eq!((err.line, err.column), (None, None));
+
+        assert_eq!(ParseError::new("empty input").to_string(), "empty input");
+    }
+
+    #[test]
+    fn test_parse_token() {
+        let input = "1, 2x";
+
+        assert_eq!(parse_token::<usize>(&input[..1], "number"), Ok(1));
+
+        let err = parse_token::<usize>(&input[2..], "number")
+            .unwrap_err()
+            .locate(input);
+        assert_eq!(err.column, Some(4));
+        assert_eq!(err.text, "2x");
+    }
+}